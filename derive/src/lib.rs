@@ -0,0 +1,127 @@
+//! Derive macros for `dbg-rs`, re-exported by it behind the `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, LitStr, Meta, ext::IdentExt, parse_macro_input};
+
+/// Implements `dbg_rs::TargetStruct` for a struct with named fields.
+///
+/// The target type is given with `#[target(type = "module!Type")]`. Fields marked with
+/// `#[field("Name")]` are read at the offset of `Name` in that type, `#[field]` alone uses the
+/// Rust field name, and the remaining fields are set to their default value. Read fields must
+/// implement `dbg_rs::Pod` and have the size of the target field.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(TargetStruct)]
+/// #[target(type = "nt!_EPROCESS")]
+/// struct Process {
+///     #[field("UniqueProcessId")]
+///     pid: u64,
+///
+///     #[field("ActiveProcessLinks")]
+///     links: [u64; 2],
+/// }
+/// ```
+#[proc_macro_derive(TargetStruct, attributes(target, field))]
+pub fn derive_target_struct(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Generates the `TargetStruct` implementation.
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(input, "TargetStruct can only be derived for structs"));
+    };
+
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(input, "TargetStruct requires named fields"));
+    };
+
+    let type_name = target_type(input)?;
+    let mut names = Vec::new();
+    let mut inits = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named fields have identifiers");
+        let ty = &field.ty;
+
+        let Some(name) = field_name(field)? else {
+            inits.push(quote! { #ident: ::core::default::Default::default() });
+            continue;
+        };
+
+        let index = names.len();
+        names.push(name);
+        inits.push(quote! {
+            #ident: {
+                let field = &fields[#index];
+                let size = ::core::mem::size_of::<#ty>();
+                if field.size as usize != size {
+                    return ::core::result::Result::Err(::dbg_rs::error::DbgError::FieldSizeMismatch {
+                        type_name: ::std::string::String::from(Self::TYPE_NAME),
+                        field: field.name.clone(),
+                        expected: field.size,
+                        actual: size,
+                    });
+                }
+
+                let addr = base.wrapping_add(u64::from(field.offset));
+                let [value] = ::dbg_rs::Memory::read_array::<#ty, 1>(memory, addr)?;
+                value
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::dbg_rs::TargetStruct for #ident #ty_generics #where_clause {
+            const TYPE_NAME: &'static str = #type_name;
+            const FIELDS: &'static [&'static str] = &[#(#names),*];
+
+            #[allow(unused_variables)]
+            fn from_fields<M: ::dbg_rs::Memory>(
+                memory: &M,
+                base: u64,
+                fields: &[::dbg_rs::FieldInfo],
+            ) -> ::core::result::Result<Self, ::dbg_rs::error::DbgError> {
+                ::core::result::Result::Ok(Self { #(#inits),* })
+            }
+        }
+    })
+}
+
+/// Reads the type name from `#[target(type = "...")]`.
+fn target_type(input: &DeriveInput) -> Result<LitStr, Error> {
+    let mut type_name = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("target")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("type") {
+                type_name = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `type = \"module!Type\"`"))
+            }
+        })?;
+    }
+
+    type_name.ok_or_else(|| Error::new_spanned(&input.ident, "missing #[target(type = \"module!Type\")]"))
+}
+
+/// Reads the target field name from `#[field("...")]` or `#[field]`.
+fn field_name(field: &syn::Field) -> Result<Option<LitStr>, Error> {
+    let Some(attr) = field.attrs.iter().find(|attr| attr.path().is_ident("field")) else {
+        return Ok(None);
+    };
+
+    match &attr.meta {
+        Meta::Path(_) => {
+            let ident = field.ident.as_ref().expect("named fields have identifiers");
+            Ok(Some(LitStr::new(&ident.unraw().to_string(), ident.span())))
+        }
+        _ => attr.parse_args::<LitStr>().map(Some),
+    }
+}
@@ -7,32 +7,17 @@ use windows::{
     },
 };
 
-/// List loaded modules, skipping unloaded ones.
+/// List loaded modules.
 fn wrap(dbg: &Dbg) -> Result<(), DbgError> {
-    let mut index = 0;
-
-    // Iterate over all modules in the process.
-    loop {
-        // Retrieve the base address of the module.
-        let base = match unsafe { dbg.symbols.GetModuleByIndex(index) } {
-            Ok(base) => base,
-            Err(_) => break, // Stop when no more modules are found.
-        };
-
-        // Get the module name from its base address.
-        let module_name = dbg.get_symbol_name(base).unwrap_or_else(|_| "<unknown>".to_string());
-
-        // Skip unloaded modules.
-        if module_name.contains("<Unloaded_") {
-            index += 1;
-            continue;
-        }
-
-        // Clean and print the module name.
-        let clean_name = module_name.split('!').next().unwrap_or(&module_name).trim();
-        dprintln!(dbg, "[dbg] Module {index}: {clean_name} (Base: {base:#X})");
-
-        index += 1;
+    for (index, module) in dbg.modules()?.iter().enumerate() {
+        dprintln!(
+            dbg,
+            "[dbg] Module {index}: {} (Base: {:#X}, Size: {:#X}, Symbols: {:?})",
+            module.module_name,
+            module.base,
+            module.size,
+            module.symbol_type
+        );
     }
 
     dprintln!(dbg, "[dbg] Finished listing modules.");
@@ -0,0 +1,371 @@
+//! Typed management of engine breakpoints.
+
+#[cfg(windows)]
+use std::ffi::CString;
+#[cfg(windows)]
+use windows::{Win32::System::Diagnostics::Debug::Extensions::*, core::PCSTR};
+#[cfg(windows)]
+use crate::error::DbgError;
+
+/// Start of the command built for conditional breakpoints, followed by the condition.
+#[cfg(any(windows, test))]
+const CONDITION_PREFIX: &str = ".if (";
+
+/// Separates the condition from the command of conditional breakpoints.
+#[cfg(any(windows, test))]
+const CONDITION_SEPARATOR: &str = ") { ";
+
+/// End of the command built for conditional breakpoints, resuming when the condition is false.
+#[cfg(any(windows, test))]
+const CONDITION_SUFFIX: &str = " } .else { gc }";
+
+/// The kind of a breakpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BreakpointKind {
+    /// A breakpoint triggered when an instruction is executed (`bp`).
+    Code,
+
+    /// A processor breakpoint triggered on memory access (`ba`).
+    Data,
+}
+
+/// The access that triggers a data breakpoint, matching the `DEBUG_BREAK_*` constants.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BreakpointAccess {
+    /// Break when the memory is read.
+    Read = 1,
+
+    /// Break when the memory is written.
+    Write = 2,
+
+    /// Break when the memory is read or written.
+    ReadWrite = 3,
+
+    /// Break when the memory is executed.
+    Execute = 4,
+
+    /// Break when the I/O port is accessed.
+    Io = 8,
+}
+
+impl TryFrom<u32> for BreakpointAccess {
+    type Error = u32;
+
+    /// Converts raw `DEBUG_BREAK_*` flags into a [`BreakpointAccess`].
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            1 => BreakpointAccess::Read,
+            2 => BreakpointAccess::Write,
+            3 => BreakpointAccess::ReadWrite,
+            4 => BreakpointAccess::Execute,
+            8 => BreakpointAccess::Io,
+            other => return Err(other),
+        })
+    }
+}
+
+/// A handle to a breakpoint owned by the debugging engine.
+///
+/// Created with `Dbg::add_breakpoint`, `Dbg::add_breakpoint_expr` or `Dbg::add_data_breakpoint`,
+/// and removed with `Dbg::remove_breakpoint`. New breakpoints start disabled so they can be
+/// configured before [`Breakpoint::enable`] is called.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let bp = dbg.add_breakpoint_expr("nt!NtCreateFile")?;
+/// bp.set_command("k; gc")?;
+/// bp.set_condition("@rcx != 0")?;
+/// bp.set_pass_count(3)?;
+/// bp.enable()?;
+/// ```
+#[cfg(windows)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// The underlying engine breakpoint.
+    pub breakpoint: IDebugBreakpoint2,
+}
+
+#[cfg(windows)]
+impl Breakpoint {
+    /// Wraps a breakpoint returned by the engine.
+    pub(crate) fn new(breakpoint: IDebugBreakpoint2) -> Self {
+        Self { breakpoint }
+    }
+
+    /// Returns the engine identifier of the breakpoint.
+    pub fn id(&self) -> Result<u32, DbgError> {
+        unsafe { Ok(self.breakpoint.GetId()?) }
+    }
+
+    /// Returns whether this is a code or a data breakpoint.
+    pub fn kind(&self) -> Result<BreakpointKind, DbgError> {
+        let (mut kind, mut processor) = (0, 0);
+        unsafe { self.breakpoint.GetType(&mut kind, &mut processor)? };
+
+        match kind {
+            DEBUG_BREAKPOINT_CODE => Ok(BreakpointKind::Code),
+            DEBUG_BREAKPOINT_DATA => Ok(BreakpointKind::Data),
+            _ => Err(DbgError::DbgGeneralError("Unknown breakpoint type")),
+        }
+    }
+
+    /// Returns the address of the breakpoint.
+    ///
+    /// Fails for deferred breakpoints whose expression could not be resolved yet.
+    pub fn offset(&self) -> Result<u64, DbgError> {
+        unsafe { Ok(self.breakpoint.GetOffset()?) }
+    }
+
+    /// Moves the breakpoint to an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The new address of the breakpoint.
+    pub fn set_offset(&self, offset: u64) -> Result<(), DbgError> {
+        unsafe { Ok(self.breakpoint.SetOffset(offset)?) }
+    }
+
+    /// Returns the expression the breakpoint location is evaluated from.
+    pub fn offset_expression(&self) -> Result<String, DbgError> {
+        let mut size = 0u32;
+        unsafe { self.breakpoint.GetOffsetExpression(None, Some(&mut size))? };
+
+        let mut buffer = vec![0u8; size as usize];
+        unsafe { self.breakpoint.GetOffsetExpression(Some(&mut buffer), None)? };
+
+        Ok(from_cstr_buffer(buffer))
+    }
+
+    /// Sets the expression the breakpoint location is evaluated from, e.g. `nt!NtCreateFile`.
+    ///
+    /// The engine re-evaluates it when modules load, so the symbol does not need to exist yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The location expression.
+    pub fn set_offset_expression(&self, expr: &str) -> Result<(), DbgError> {
+        let cstr = CString::new(expr)?;
+        unsafe { Ok(self.breakpoint.SetOffsetExpression(PCSTR(cstr.as_ptr().cast()))?) }
+    }
+
+    /// Returns the size and access type of a data breakpoint.
+    pub fn data_parameters(&self) -> Result<(u32, BreakpointAccess), DbgError> {
+        let (mut size, mut access) = (0, 0);
+        unsafe { self.breakpoint.GetDataParameters(&mut size, &mut access)? };
+
+        let access = BreakpointAccess::try_from(access).map_err(|_| DbgError::DbgGeneralError("Unknown breakpoint access type"))?;
+        Ok((size, access))
+    }
+
+    /// Sets the size and access type of a data breakpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The number of bytes watched: 1, 2, 4 or 8.
+    /// * `access` - The access that triggers the breakpoint.
+    pub fn set_data_parameters(&self, size: u32, access: BreakpointAccess) -> Result<(), DbgError> {
+        if !matches!(size, 1 | 2 | 4 | 8) {
+            return Err(DbgError::InvalidSize(size as usize));
+        }
+
+        unsafe { Ok(self.breakpoint.SetDataParameters(size, access as u32)?) }
+    }
+
+    /// Returns whether the breakpoint is enabled.
+    pub fn is_enabled(&self) -> Result<bool, DbgError> {
+        Ok(self.flags()? & DEBUG_BREAKPOINT_ENABLED != 0)
+    }
+
+    /// Enables the breakpoint.
+    pub fn enable(&self) -> Result<(), DbgError> {
+        unsafe { Ok(self.breakpoint.AddFlags(DEBUG_BREAKPOINT_ENABLED)?) }
+    }
+
+    /// Disables the breakpoint without removing it.
+    pub fn disable(&self) -> Result<(), DbgError> {
+        unsafe { Ok(self.breakpoint.RemoveFlags(DEBUG_BREAKPOINT_ENABLED)?) }
+    }
+
+    /// Returns whether the breakpoint is removed after it is hit once.
+    pub fn is_one_shot(&self) -> Result<bool, DbgError> {
+        Ok(self.flags()? & DEBUG_BREAKPOINT_ONE_SHOT != 0)
+    }
+
+    /// Makes the breakpoint remove itself after it is hit once, or not.
+    ///
+    /// # Arguments
+    ///
+    /// * `one_shot` - Whether the breakpoint is a one-shot breakpoint.
+    pub fn set_one_shot(&self, one_shot: bool) -> Result<(), DbgError> {
+        unsafe {
+            if one_shot {
+                self.breakpoint.AddFlags(DEBUG_BREAKPOINT_ONE_SHOT)?;
+            } else {
+                self.breakpoint.RemoveFlags(DEBUG_BREAKPOINT_ONE_SHOT)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of hits required before the breakpoint triggers.
+    pub fn pass_count(&self) -> Result<u32, DbgError> {
+        unsafe { Ok(self.breakpoint.GetPassCount()?) }
+    }
+
+    /// Returns the number of hits remaining before the breakpoint triggers.
+    pub fn current_pass_count(&self) -> Result<u32, DbgError> {
+        unsafe { Ok(self.breakpoint.GetCurrentPassCount()?) }
+    }
+
+    /// Sets the number of hits required before the breakpoint triggers.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of hits, where 1 triggers on every hit.
+    pub fn set_pass_count(&self, count: u32) -> Result<(), DbgError> {
+        unsafe { Ok(self.breakpoint.SetPassCount(count)?) }
+    }
+
+    /// Returns the engine thread the breakpoint is restricted to, if any.
+    pub fn thread(&self) -> Result<Option<u32>, DbgError> {
+        // The engine reports an error when the breakpoint matches every thread
+        unsafe { Ok(self.breakpoint.GetMatchThreadId().ok()) }
+    }
+
+    /// Restricts the breakpoint to a single thread, or lets it trigger on any thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `thread` - The engine thread identifier, or `None` for every thread.
+    pub fn set_thread(&self, thread: Option<u32>) -> Result<(), DbgError> {
+        unsafe { Ok(self.breakpoint.SetMatchThreadId(thread.unwrap_or(DEBUG_ANY_ID))?) }
+    }
+
+    /// Returns the command executed when the breakpoint triggers, without its condition.
+    pub fn command(&self) -> Result<String, DbgError> {
+        let raw = self.raw_command()?;
+        Ok(split_conditional(&raw).1.to_string())
+    }
+
+    /// Sets the command executed when the breakpoint triggers, keeping its condition.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The debugger command, e.g. `k; gc`.
+    pub fn set_command(&self, command: &str) -> Result<(), DbgError> {
+        self.apply(self.condition()?.as_deref(), command)
+    }
+
+    /// Returns the condition set with [`Breakpoint::set_condition`], if any.
+    pub fn condition(&self) -> Result<Option<String>, DbgError> {
+        let raw = self.raw_command()?;
+        Ok(split_conditional(&raw).0.map(str::to_string))
+    }
+
+    /// Makes the breakpoint break only when `condition` evaluates to a non-zero value,
+    /// replacing any previous condition and keeping the command.
+    ///
+    /// The engine has no native breakpoint conditions, so the engine command becomes
+    /// `.if (condition) { command } .else { gc }`. [`Breakpoint::command`] and
+    /// [`Breakpoint::condition`] still return each part on its own.
+    ///
+    /// # Arguments
+    ///
+    /// * `condition` - A MASM expression, e.g. `@rcx == 0`.
+    pub fn set_condition(&self, condition: &str) -> Result<(), DbgError> {
+        self.apply(Some(condition), &self.command()?)
+    }
+
+    /// Removes the condition, so the breakpoint breaks on every hit again.
+    pub fn remove_condition(&self) -> Result<(), DbgError> {
+        self.apply(None, &self.command()?)
+    }
+
+    /// Returns the command stored in the engine, including the condition wrapper.
+    fn raw_command(&self) -> Result<String, DbgError> {
+        let mut size = 0u32;
+        unsafe { self.breakpoint.GetCommand(None, Some(&mut size))? };
+
+        let mut buffer = vec![0u8; size as usize];
+        unsafe { self.breakpoint.GetCommand(Some(&mut buffer), None)? };
+
+        Ok(from_cstr_buffer(buffer))
+    }
+
+    /// Stores a command and an optional condition in the engine.
+    fn apply(&self, condition: Option<&str>, command: &str) -> Result<(), DbgError> {
+        let cstr = CString::new(build_conditional(condition, command))?;
+        unsafe { Ok(self.breakpoint.SetCommand(PCSTR(cstr.as_ptr().cast()))?) }
+    }
+
+    /// Returns the raw `DEBUG_BREAKPOINT_*` flags.
+    fn flags(&self) -> Result<u32, DbgError> {
+        unsafe { Ok(self.breakpoint.GetFlags()?) }
+    }
+}
+
+/// Builds the engine command for a breakpoint command and an optional condition.
+#[cfg(any(windows, test))]
+fn build_conditional(condition: Option<&str>, command: &str) -> String {
+    match condition {
+        Some(condition) => format!("{CONDITION_PREFIX}{condition}{CONDITION_SEPARATOR}{command}{CONDITION_SUFFIX}"),
+        None => command.to_string(),
+    }
+}
+
+/// Splits an engine command built by [`build_conditional`] into its condition and command.
+///
+/// Commands that were not built with a condition are returned as they are.
+#[cfg(any(windows, test))]
+fn split_conditional(raw: &str) -> (Option<&str>, &str) {
+    // MASM conditions cannot contain braces, so the first separator ends the condition
+    raw.strip_prefix(CONDITION_PREFIX)
+        .and_then(|rest| rest.strip_suffix(CONDITION_SUFFIX))
+        .and_then(|rest| rest.split_once(CONDITION_SEPARATOR))
+        .map_or((None, raw), |(condition, command)| (Some(condition), command))
+}
+
+/// Converts a null-terminated buffer filled by the engine into a [`String`].
+#[cfg(windows)]
+fn from_cstr_buffer(mut buffer: Vec<u8>) -> String {
+    if let Some(len) = buffer.iter().position(|&b| b == 0) {
+        buffer.truncate(len);
+    }
+
+    String::from_utf8_lossy(&buffer).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_condition_and_command_apart() {
+        let raw = build_conditional(Some("@rcx == 0"), "k; gc");
+        assert_eq!(raw, ".if (@rcx == 0) { k; gc } .else { gc }");
+        assert_eq!(split_conditional(&raw), (Some("@rcx == 0"), "k; gc"));
+
+        // Replacing either part rebuilds the command instead of nesting wrappers
+        let (_, command) = split_conditional(&raw);
+        let raw = build_conditional(Some("poi(@rdx) != 0"), command);
+        assert_eq!(split_conditional(&raw), (Some("poi(@rdx) != 0"), "k; gc"));
+
+        let (condition, _) = split_conditional(&raw);
+        let raw = build_conditional(condition, "r rax");
+        assert_eq!(raw, ".if (poi(@rdx) != 0) { r rax } .else { gc }");
+    }
+
+    #[test]
+    fn handles_unconditional_commands() {
+        assert_eq!(build_conditional(None, "k"), "k");
+        assert_eq!(split_conditional("k; gc"), (None, "k; gc"));
+        assert_eq!(split_conditional(""), (None, ""));
+        assert_eq!(split_conditional(&build_conditional(Some("1"), "")), (Some("1"), ""));
+
+        // A hand-written `.if` without the `.else { gc }` is left untouched
+        assert_eq!(split_conditional(".if (1) { k }"), (None, ".if (1) { k }"));
+    }
+}
@@ -0,0 +1,342 @@
+//! Caching reads of target memory at page granularity.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+#[cfg(windows)]
+use windows::{
+    Win32::System::Diagnostics::Debug::{EXCEPTION_RECORD64, Extensions::*},
+    core::{PCSTR, Ref, implement},
+};
+use crate::{error::DbgError, ptr::PointerWidth, target::Memory};
+
+/// Granularity at which memory is fetched and cached.
+const PAGE_SIZE: u64 = 0x1000;
+
+/// Counters describing how a [`CachedMemory`] has been used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CacheStats {
+    /// Page lookups served from the cache.
+    pub hits: u64,
+
+    /// Page lookups that had to read from the target.
+    pub misses: u64,
+
+    /// Times the whole cache was dropped.
+    pub invalidations: u64,
+}
+
+/// A reader fetching whole pages from another [`Memory`] and serving later reads from them.
+///
+/// The cache is dropped whenever [`Memory::generation`] of the underlying memory changes,
+/// which for `Dbg` happens when the target resumes or memory is written. Pages the target
+/// reports as unreadable with [`DbgError::InvalidAddress`] are cached too, so probing unmapped
+/// memory does not reach the target again. Any other error, such as a lost connection, is
+/// returned and nothing is cached.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let cache = CachedMemory::new(&dbg);
+/// let mut entry = cache.read_ptr(dbg.get_symbol_address("nt!PsActiveProcessHead")?)?;
+/// // ... walk the list through `cache` ...
+/// dprintln!(dbg, "{:?}", cache.stats());
+/// ```
+#[derive(Debug)]
+pub struct CachedMemory<'a, M: Memory + ?Sized> {
+    /// The memory the pages are fetched from.
+    inner: &'a M,
+
+    /// The cached pages, keyed by their base address. Unreadable parts are left out.
+    pages: RefCell<HashMap<u64, Rc<[u8]>>>,
+
+    /// The generation of `inner` the cached pages belong to.
+    generation: Cell<u64>,
+
+    /// Usage counters.
+    stats: Cell<CacheStats>,
+}
+
+impl<'a, M: Memory + ?Sized> CachedMemory<'a, M> {
+    /// Creates an empty cache in front of `inner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The memory to read pages from.
+    pub fn new(inner: &'a M) -> Self {
+        Self {
+            inner,
+            pages: RefCell::new(HashMap::new()),
+            generation: Cell::new(inner.generation()),
+            stats: Cell::new(CacheStats::default()),
+        }
+    }
+
+    /// Returns the memory the pages are fetched from.
+    pub fn inner(&self) -> &'a M {
+        self.inner
+    }
+
+    /// Returns the usage counters collected so far.
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+
+    /// Drops every cached page.
+    pub fn invalidate(&self) {
+        self.pages.borrow_mut().clear();
+        self.update_stats(|stats| stats.invalidations += 1);
+    }
+
+    /// Returns the readable bytes of the page at `base`, reading it on a miss.
+    fn page(&self, base: u64) -> Result<Rc<[u8]>, DbgError> {
+        if let Some(page) = self.pages.borrow().get(&base) {
+            self.update_stats(|stats| stats.hits += 1);
+            return Ok(page.clone());
+        }
+
+        self.update_stats(|stats| stats.misses += 1);
+        let mut buffer = vec![0u8; PAGE_SIZE as usize];
+        let read = match self.inner.read_vaddr(base, &mut buffer) {
+            Ok(read) => read,
+            Err(DbgError::InvalidAddress(_)) => 0,
+            Err(err) => return Err(err),
+        };
+        buffer.truncate(read);
+
+        let page: Rc<[u8]> = buffer.into();
+        self.pages.borrow_mut().insert(base, page.clone());
+        Ok(page)
+    }
+
+    /// Applies `f` to the usage counters.
+    fn update_stats(&self, f: impl FnOnce(&mut CacheStats)) {
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+    }
+}
+
+impl<M: Memory + ?Sized> Memory for CachedMemory<'_, M> {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        let generation = self.inner.generation();
+        if generation != self.generation.get() {
+            self.invalidate();
+            self.generation.set(generation);
+        }
+
+        let mut read = 0;
+        while read < buffer.len() {
+            let addr = vaddr.wrapping_add(read as u64);
+            let offset = (addr % PAGE_SIZE) as usize;
+            let page = self.page(addr - offset as u64)?;
+
+            // Stop at the first byte the page could not provide
+            let len = page.len().saturating_sub(offset).min(buffer.len() - read);
+            buffer[read..read + len].copy_from_slice(&page[offset..offset + len]);
+            read += len;
+
+            if offset + len < PAGE_SIZE as usize && read < buffer.len() {
+                break;
+            }
+        }
+
+        if read == 0 && !buffer.is_empty() {
+            return Err(DbgError::InvalidAddress(vaddr));
+        }
+
+        Ok(read)
+    }
+
+    fn generation(&self) -> u64 {
+        self.inner.generation()
+    }
+
+    fn pointer_width(&self) -> Result<PointerWidth, DbgError> {
+        self.inner.pointer_width()
+    }
+}
+
+/// An `IDebugEventCallbacks` implementation bumping a counter whenever the target
+/// resumes, switches context or has its memory changed.
+#[cfg(windows)]
+#[implement(IDebugEventCallbacks)]
+pub(crate) struct StateWatcher {
+    /// The counter shared with the `Dbg` that installed the callbacks.
+    pub(crate) generation: Rc<Cell<u64>>,
+}
+
+#[cfg(windows)]
+impl StateWatcher_Impl {
+    /// Bumps the counter.
+    fn bump(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
+}
+
+#[cfg(windows)]
+impl IDebugEventCallbacks_Impl for StateWatcher_Impl {
+    fn GetInterestMask(&self) -> windows::core::Result<u32> {
+        Ok(DEBUG_EVENT_CHANGE_DEBUGGEE_STATE | DEBUG_EVENT_CHANGE_ENGINE_STATE)
+    }
+
+    fn Breakpoint(&self, _bp: Ref<'_, IDebugBreakpoint>) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn Exception(&self, _exception: *const EXCEPTION_RECORD64, _firstchance: u32) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn CreateThread(&self, _handle: u64, _dataoffset: u64, _startoffset: u64) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn ExitThread(&self, _exitcode: u32) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn CreateProcessA(
+        &self,
+        _imagefilehandle: u64,
+        _handle: u64,
+        _baseoffset: u64,
+        _modulesize: u32,
+        _modulename: &PCSTR,
+        _imagename: &PCSTR,
+        _checksum: u32,
+        _timedatestamp: u32,
+        _initialthreadhandle: u64,
+        _threaddataoffset: u64,
+        _startoffset: u64,
+    ) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn ExitProcess(&self, _exitcode: u32) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn LoadModule(
+        &self,
+        _imagefilehandle: u64,
+        _baseoffset: u64,
+        _modulesize: u32,
+        _modulename: &PCSTR,
+        _imagename: &PCSTR,
+        _checksum: u32,
+        _timedatestamp: u32,
+    ) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn UnloadModule(&self, _imagebasename: &PCSTR, _baseoffset: u64) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn SystemError(&self, _error: u32, _level: u32) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn SessionStatus(&self, _status: u32) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn ChangeDebuggeeState(&self, flags: u32, _argument: u64) -> windows::core::Result<()> {
+        if flags & DEBUG_CDS_DATA != 0 {
+            self.bump();
+        }
+
+        Ok(())
+    }
+
+    fn ChangeEngineState(&self, flags: u32, _argument: u64) -> windows::core::Result<()> {
+        if flags & (DEBUG_CES_EXECUTION_STATUS | DEBUG_CES_CURRENT_THREAD) != 0 {
+            self.bump();
+        }
+
+        Ok(())
+    }
+
+    fn ChangeSymbolState(&self, _flags: u32, _argument: u64) -> windows::core::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockTarget;
+
+    /// Memory failing every read with an error other than an unreadable address.
+    struct Disconnected;
+
+    impl Memory for Disconnected {
+        fn read_vaddr(&self, _vaddr: u64, _buffer: &mut [u8]) -> Result<usize, DbgError> {
+            Err(DbgError::GdbError("connection lost".to_string()))
+        }
+    }
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let target = MockTarget::new().with_memory(0x1000, (0..=255).cycle().take(0x2000).collect());
+        let cache = CachedMemory::new(&target);
+
+        assert_eq!(cache.read_array::<u8, 2>(0x1010).unwrap(), [0x10, 0x11]);
+        assert_eq!(cache.read_array::<u8, 2>(0x1020).unwrap(), [0x20, 0x21]);
+
+        // A read spanning two pages fetches the second one
+        assert_eq!(cache.read_array::<u8, 4>(0x1FFE).unwrap(), [0xFE, 0xFF, 0x00, 0x01]);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 2,
+                misses: 2,
+                invalidations: 0
+            }
+        );
+    }
+
+    #[test]
+    fn caches_unreadable_pages() {
+        let target = MockTarget::new().with_memory(0x1000, vec![0xAA; 0x800]);
+        let cache = CachedMemory::new(&target);
+
+        assert!(matches!(cache.read_array::<u8, 4>(0x3000), Err(DbgError::InvalidAddress(0x3000))));
+        assert!(matches!(cache.read_array::<u8, 4>(0x3000), Err(DbgError::InvalidAddress(0x3000))));
+        assert!(matches!(cache.read_array::<u8, 4>(0x17FE), Err(DbgError::PartialRead { requested: 4, read: 2 })));
+        assert_eq!(cache.stats().misses, 2);
+        assert_eq!(cache.stats().hits, 1);
+    }
+
+    #[test]
+    fn propagates_other_errors() {
+        let cache = CachedMemory::new(&Disconnected);
+        assert!(matches!(cache.read_array::<u8, 4>(0x1000), Err(DbgError::GdbError(_))));
+        assert!(matches!(cache.read_array::<u8, 4>(0x1000), Err(DbgError::GdbError(_))));
+        assert_eq!(cache.stats().misses, 2);
+    }
+
+    #[test]
+    fn invalidates_when_the_target_changes() {
+        let target = MockTarget::new().with_memory(0x1000, vec![1; 0x1000]);
+        let cache = CachedMemory::new(&target);
+        assert_eq!(cache.read_array::<u8, 1>(0x1000).unwrap(), [1]);
+
+        // Writing through the target bumps its generation, so the page is fetched again
+        target.write_vaddr(0x1000, &[2]).unwrap();
+        assert_eq!(cache.read_array::<u8, 1>(0x1000).unwrap(), [2]);
+        assert_eq!(cache.stats().invalidations, 1);
+
+        target.resume();
+        assert_eq!(cache.read_array::<u8, 1>(0x1001).unwrap(), [1]);
+        assert_eq!(cache.stats().invalidations, 2);
+        assert_eq!(cache.stats().misses, 3);
+
+        cache.invalidate();
+        assert_eq!(cache.stats().invalidations, 3);
+        assert_eq!(cache.generation(), target.generation());
+    }
+}
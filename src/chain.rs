@@ -0,0 +1,345 @@
+//! Resolving multi-level pointer chains such as `[[ntdll!LdrpHeap+0x10]+0x28]+8`.
+
+use std::fmt;
+use crate::{
+    error::DbgError,
+    pod::Pod,
+    target::{Memory, Symbols},
+};
+
+/// A sum of terms, each added or subtracted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Expr {
+    /// The terms and whether each one is subtracted.
+    terms: Vec<(bool, Term)>,
+}
+
+/// A single operand of an expression.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Term {
+    /// An address given as a number.
+    Number(u64),
+
+    /// A symbol resolved through [`Symbols::get_symbol_address`].
+    Symbol(String),
+
+    /// The pointer stored at the address of the inner expression.
+    Deref(Box<Expr>),
+}
+
+/// A chain of pointer dereferences leading to an address.
+///
+/// Chains are written like debugger expressions, where `[expr]` reads the pointer stored at
+/// `expr`. Numbers are hexadecimal, with an optional `0x` prefix and backticks between digits
+/// as in ``fffff803`1234abcd``, unless prefixed with `0n`. Like in the debugger, any atom made
+/// only of hexadecimal digits is a number, so such symbols must be qualified, e.g. `app!cafe`.
+/// Pointers are read with the width reported by [`Memory::pointer_width`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let chain = PointerChain::parse("[[ntdll!LdrpHeap+0x10]+0x28]+8")?;
+/// let addr = chain.resolve(&dbg)?;
+/// let flags = chain.read::<u32, _>(&dbg)?;
+///
+/// // The same chain written as a base and a list of offsets
+/// let chain = PointerChain::from_offsets("ntdll!LdrpHeap", &[0x10, 0x28, 8])?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PointerChain {
+    /// The parsed chain.
+    expr: Expr,
+}
+
+impl PointerChain {
+    /// Parses a chain written as an expression, e.g. `[[ntdll!LdrpHeap+0x10]+0x28]+8`.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain` - The expression to parse.
+    pub fn parse(chain: &str) -> Result<Self, DbgError> {
+        let mut parser = Parser { input: chain, pos: 0 };
+        let expr = parser.expr()?;
+
+        parser.skip_whitespace();
+        if parser.pos != chain.len() {
+            return Err(parser.error());
+        }
+
+        Ok(Self { expr })
+    }
+
+    /// Builds a chain from a base and a list of offsets, in the style of cheat tables.
+    ///
+    /// Every offset but the last is added and then dereferenced, and the last one is added to
+    /// the final pointer: `base` with `[o1, o2, o3]` is `[[base+o1]+o2]+o3`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The base address, as a symbol or a number.
+    /// * `offsets` - The offsets applied at each level.
+    pub fn from_offsets(base: &str, offsets: &[i64]) -> Result<Self, DbgError> {
+        let mut expr = Self::parse(base)?.expr;
+        for (index, &offset) in offsets.iter().enumerate() {
+            expr.terms.push((offset < 0, Term::Number(offset.unsigned_abs())));
+            if index + 1 < offsets.len() {
+                expr = Expr {
+                    terms: vec![(false, Term::Deref(Box::new(expr)))],
+                };
+            }
+        }
+
+        Ok(Self { expr })
+    }
+
+    /// Follows the chain and returns the final address.
+    ///
+    /// Fails with [`DbgError::PointerChain`] naming the dereference that could not be read,
+    /// counting from 1 in the order the reads happen.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target providing memory and symbols.
+    pub fn resolve<T>(&self, target: &T) -> Result<u64, DbgError>
+    where
+        T: Memory + Symbols + ?Sized,
+    {
+        let mut step = 0;
+        eval(&self.expr, target, &mut step)
+    }
+
+    /// Follows the chain and reads the value stored at the final address.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target providing memory and symbols.
+    pub fn read<V, T>(&self, target: &T) -> Result<V, DbgError>
+    where
+        V: Pod,
+        T: Memory + Symbols,
+    {
+        let [value] = target.read_array::<V, 1>(self.resolve(target)?)?;
+        Ok(value)
+    }
+}
+
+impl fmt::Display for PointerChain {
+    /// Formats the chain back as an expression.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expr)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (negative, term)) in self.terms.iter().enumerate() {
+            match (index, negative) {
+                (0, false) => {}
+                (_, true) => write!(f, "-")?,
+                (_, false) => write!(f, "+")?,
+            }
+
+            match term {
+                Term::Number(n) => write!(f, "{n:#x}")?,
+                Term::Symbol(name) => write!(f, "{name}")?,
+                Term::Deref(inner) => write!(f, "[{inner}]")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Evaluates an expression, counting dereferences in `step`.
+fn eval<T>(expr: &Expr, target: &T, step: &mut usize) -> Result<u64, DbgError>
+where
+    T: Memory + Symbols + ?Sized,
+{
+    expr.terms.iter().try_fold(0u64, |acc, (negative, term)| {
+        let value = match term {
+            Term::Number(n) => *n,
+            Term::Symbol(name) => target.get_symbol_address(name)?,
+            Term::Deref(inner) => {
+                let addr = eval(inner, target, step)?;
+                *step += 1;
+                target
+                    .read_ptr(addr)
+                    .map_err(|source| DbgError::PointerChain {
+                        step: *step,
+                        addr,
+                        source: Box::new(source),
+                    })?
+                    .addr()
+            }
+        };
+
+        Ok(if *negative { acc.wrapping_sub(value) } else { acc.wrapping_add(value) })
+    })
+}
+
+/// A recursive descent parser for chain expressions.
+struct Parser<'a> {
+    /// The whole expression.
+    input: &'a str,
+
+    /// The byte offset of the next character.
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// Parses `term (('+' | '-') term)*`.
+    fn expr(&mut self) -> Result<Expr, DbgError> {
+        let mut terms = vec![(false, self.term()?)];
+        loop {
+            self.skip_whitespace();
+            let negative = match self.peek() {
+                Some('+') => false,
+                Some('-') => true,
+                _ => return Ok(Expr { terms }),
+            };
+
+            self.pos += 1;
+            terms.push((negative, self.term()?));
+        }
+    }
+
+    /// Parses `'[' expr ']'`, a number or a symbol.
+    fn term(&mut self) -> Result<Term, DbgError> {
+        self.skip_whitespace();
+        if self.peek() == Some('[') {
+            self.pos += 1;
+            let inner = self.expr()?;
+
+            self.skip_whitespace();
+            if self.peek() != Some(']') {
+                return Err(self.error());
+            }
+
+            self.pos += 1;
+            return Ok(Term::Deref(Box::new(inner)));
+        }
+
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '+' | '-'))
+            .unwrap_or(rest.len());
+        let atom = &rest[..len];
+        if atom.is_empty() {
+            return Err(self.error());
+        }
+
+        let term = if atom.starts_with(|c: char| c.is_ascii_digit()) || is_hex_literal(atom) {
+            Term::Number(parse_number(atom).ok_or_else(|| self.error())?)
+        } else {
+            Term::Symbol(atom.to_string())
+        };
+
+        self.pos += len;
+        Ok(term)
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    /// Skips any whitespace at the current position.
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Builds the error returned for a malformed expression, pointing at the current position.
+    fn error(&self) -> DbgError {
+        let found = self.peek().map_or_else(|| "end of input".to_string(), |c| format!("'{c}'"));
+        DbgError::InvalidExpression(format!("{}: unexpected {found} at position {}", self.input, self.pos))
+    }
+}
+
+/// Returns whether an atom not starting with a digit is still a number, like `deadbeef`.
+fn is_hex_literal(atom: &str) -> bool {
+    atom.chars().all(|c| c.is_ascii_hexdigit() || c == '`') && atom.chars().any(|c| c.is_ascii_hexdigit())
+}
+
+/// Parses a hexadecimal number, or a decimal one prefixed with `0n`.
+fn parse_number(s: &str) -> Option<u64> {
+    if let Some(decimal) = s.strip_prefix("0n") {
+        return decimal.parse().ok();
+    }
+
+    let hex = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    u64::from_str_radix(&hex.replace('`', ""), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a chain and returns the message of the error it fails with.
+    fn error(chain: &str) -> String {
+        match PointerChain::parse(chain) {
+            Err(DbgError::InvalidExpression(message)) => message,
+            other => panic!("{chain} parsed as {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_numbers() {
+        let number = |chain: &str| match PointerChain::parse(chain).unwrap().expr.terms.as_slice() {
+            [(false, Term::Number(n))] => *n,
+            terms => panic!("{chain} parsed as {terms:?}"),
+        };
+
+        assert_eq!(number("0x10"), 0x10);
+        assert_eq!(number("0X10"), 0x10);
+        assert_eq!(number("10"), 0x10);
+        assert_eq!(number("0n10"), 10);
+        assert_eq!(number("deadbeef"), 0xDEAD_BEEF);
+        assert_eq!(number("fffff803`1234abcd"), 0xFFFF_F803_1234_ABCD);
+        assert_eq!(number("0xfffff803`1234abcd"), 0xFFFF_F803_1234_ABCD);
+    }
+
+    #[test]
+    fn parses_symbols() {
+        let chain = PointerChain::parse("ntdll!LdrpHeap - app!cafe + nt!_KPRCB").unwrap();
+        let symbols = chain
+            .expr
+            .terms
+            .iter()
+            .map(|(negative, term)| match term {
+                Term::Symbol(name) => (*negative, name.as_str()),
+                term => panic!("unexpected term {term:?}"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(symbols, [(false, "ntdll!LdrpHeap"), (true, "app!cafe"), (false, "nt!_KPRCB")]);
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        for (chain, display) in [
+            ("[[ntdll!LdrpHeap+0x10]+0x28]+8", "[[ntdll!LdrpHeap+0x10]+0x28]+0x8"),
+            ("[ fffff803`1234abcd ] - 0n16", "[0xfffff8031234abcd]-0x10"),
+            ("[[[nt!PsInitialSystemProcess]]]", "[[[nt!PsInitialSystemProcess]]]"),
+        ] {
+            let parsed = PointerChain::parse(chain).unwrap();
+            assert_eq!(parsed.to_string(), display);
+            assert_eq!(PointerChain::parse(display).unwrap(), parsed);
+        }
+
+        let chain = PointerChain::from_offsets("app!Base", &[0x10, -8, 4]).unwrap();
+        assert_eq!(chain.to_string(), "[[app!Base+0x10]-0x8]+0x4");
+        assert_eq!(PointerChain::parse(&chain.to_string()).unwrap(), chain);
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        assert_eq!(error(""), ": unexpected end of input at position 0");
+        assert_eq!(error("[app!Base+0x10"), "[app!Base+0x10: unexpected end of input at position 14");
+        assert_eq!(error("app!Base+"), "app!Base+: unexpected end of input at position 9");
+        assert_eq!(error("[app!Base]]"), "[app!Base]]: unexpected ']' at position 10");
+        assert_eq!(error("1 + 0xzz"), "1 + 0xzz: unexpected '0' at position 4");
+        assert_eq!(error("0n12ab"), "0n12ab: unexpected '0' at position 0");
+        assert_eq!(error("[]"), "[]: unexpected ']' at position 1");
+    }
+}
@@ -1,48 +1,67 @@
-use std::{ffi::CString, path::PathBuf};
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    ffi::CString,
+    path::PathBuf,
+    rc::Rc,
+    time::Duration,
+};
 use windows::{
     Win32::System::Diagnostics::Debug::Extensions::*,
-    core::{IUnknown, Interface, PCSTR},
+    core::{HRESULT, IUnknown, Interface, PCSTR},
+};
+use crate::{
+    breakpoint::{Breakpoint, BreakpointAccess},
+    cache::StateWatcher,
+    error::DbgError,
+    event::{EventBridge, EventHandler, ExecutionStatus},
+    module::{Module, ModuleInfo, SymbolType},
+    output::{CapturedOutput, OutputCapture},
+    pattern::Pattern,
+    pod::{Pod, as_bytes},
+    ptr::PointerWidth,
+    string::TargetString,
+    region::Regions,
+    snapshot::Snapshot,
+    space::{MemorySpace, PhysicalCaching},
+    symbol::{SymbolAt, SymbolMatches},
+    target::{Commands, Memory, PhysicalMemory, Registers, Symbols, Types},
+    types::{FieldInfo, TypeInfo},
+    value::{DebugValue, Value, ValueType},
 };
-use crate::error::DbgError;
-
-/// Macro to send formatted messages to the debugger using [`Dbg::println`].
-///
-/// # Examples
-///
-/// ```rust,ignore
-/// dprintln!(dbg, "Hello, {}!", "Debugger");
-/// dprintln!(dbg, "This is a number: {}", 42);
-/// ```
-#[macro_export]
-macro_rules! dprintln {
-    ($dbg:expr) => {
-        $dbg.println("");
-    };
-
-    ($dbg:expr, $($arg:tt)*) => {
-        $dbg.println(format!($($arg)*));
-    };
-}
 
-/// Macro to send formatted messages to the debugger using [`Dbg::print`]
-///  
-/// # Examples
-///
-/// ```rust,ignore
-/// dprint!(dbg, "Hello, {}!", "Debugger");
-/// dprint!(dbg, "This is a number: {}", 42);
-/// dprint!(dbg); // Prints an empty message
-/// ```
-#[macro_export]
-macro_rules! dprint {
-    ($dbg:expr) => {
-        $dbg.print("");
-    };
-
-    ($dbg:expr, $($arg:tt)*) => {
-        $dbg.print(format!($($arg)*));
-    };
-}
+/// `IMAGE_FILE_MACHINE_I386`.
+const IMAGE_FILE_MACHINE_I386: u32 = 0x014C;
+
+/// `IMAGE_FILE_MACHINE_ARM`.
+const IMAGE_FILE_MACHINE_ARM: u32 = 0x01C0;
+
+/// `IMAGE_FILE_MACHINE_THUMB`.
+const IMAGE_FILE_MACHINE_THUMB: u32 = 0x01C2;
+
+/// `IMAGE_FILE_MACHINE_ARMNT`.
+const IMAGE_FILE_MACHINE_ARMNT: u32 = 0x01C4;
+
+/// `IMAGE_FILE_MACHINE_AMD64`.
+const IMAGE_FILE_MACHINE_AMD64: u32 = 0x8664;
+
+/// `IMAGE_FILE_MACHINE_ARM64`.
+const IMAGE_FILE_MACHINE_ARM64: u32 = 0xAA64;
+
+/// `E_FAIL`, returned when no symbol matches an address.
+const E_FAIL: HRESULT = HRESULT(0x8000_4005_u32 as i32);
+
+/// `HRESULT_FROM_WIN32(ERROR_READ_FAULT)`, returned when virtual memory cannot be read.
+const E_READ_FAULT: HRESULT = HRESULT(0x8007_001E_u32 as i32);
+
+/// `HRESULT_FROM_WIN32(ERROR_PARTIAL_COPY)`, returned when a live process refuses a read.
+const E_PARTIAL_COPY: HRESULT = HRESULT(0x8007_012B_u32 as i32);
+
+/// `E_INVALIDARG`, returned when a field index lies past the last field of a type.
+const E_INVALIDARG: HRESULT = HRESULT(0x8007_0057_u32 as i32);
+
+/// `E_NOINTERFACE`, returned when no symbol lies at the requested distance, no memory
+/// region lies above an address or a type has no such field.
+pub(crate) const E_NOINTERFACE: HRESULT = HRESULT(0x8000_4002_u32 as i32);
 
 /// Represents a debugging interface that allows execution of commands,
 /// querying and managing debug symbols, inspecting memory, and interacting with registers.
@@ -59,6 +78,21 @@ pub struct Dbg {
 
     /// An interface to query and manipulate CPU registers in the debugged target.
     pub registers: IDebugRegisters,
+
+    /// The debugging client, used to manage output and event callbacks.
+    client: IDebugClient,
+
+    /// The version of the control interface providing the `*2` breakpoint methods.
+    control4: IDebugControl4,
+
+    /// The event callbacks registered through [`Dbg::set_event_handler`], kept alive while installed.
+    event_callbacks: RefCell<Option<IDebugEventCallbacks>>,
+
+    /// Bumped whenever the target resumes or memory is written, see [`Memory::generation`].
+    generation: Rc<Cell<u64>>,
+
+    /// A dedicated client notifying state changes made outside this instance, created on first use.
+    state_watcher: OnceCell<Option<(IDebugClient, IDebugEventCallbacks)>>,
 }
 
 impl Dbg {
@@ -81,9 +115,27 @@ impl Dbg {
             symbols: client.cast()?,
             dataspaces: client.cast()?,
             registers: client.cast()?,
+            client: client.cast()?,
+            control4: client.cast()?,
+            event_callbacks: RefCell::new(None),
+            generation: Rc::new(Cell::new(0)),
+            state_watcher: OnceCell::new(),
         })
     }
 
+    /// Returns the debugging client this instance was created from.
+    pub fn client(&self) -> &IDebugClient {
+        &self.client
+    }
+
+    /// Returns the `IDebugControl4` interface of the client, which [`Dbg::control`] predates.
+    ///
+    /// [`Dbg::control`] stays an `IDebugControl3` for compatibility; use this for the newer
+    /// methods, such as the `*2` breakpoint methods.
+    pub fn control4(&self) -> &IDebugControl4 {
+        &self.control4
+    }
+
     /// Creates a new debugger instance of the specified type.
     ///
     /// # Example
@@ -111,6 +163,8 @@ impl Dbg {
     where
         S: Into<String>,
     {
+        // Any command may resume the target or edit its memory
+        self.bump_generation();
         let cstr = CString::new(command.into())?;
         unsafe {
             Ok(self
@@ -119,6 +173,353 @@ impl Dbg {
         }
     }
 
+    /// Executes a command in the debugger and returns the text it printed.
+    ///
+    /// The output is captured by temporary output callbacks installed on this client,
+    /// so it is not displayed in the debugger. The previous callbacks and output mask
+    /// are restored afterwards, even if the command fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to execute, provided as a string.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let output = dbg.exec_capture("lm")?;
+    /// for line in output.normal.lines() {
+    ///     dprintln!(dbg, "> {line}");
+    /// }
+    /// ```
+    pub fn exec_capture<S>(&self, command: S) -> Result<CapturedOutput, DbgError>
+    where
+        S: Into<String>,
+    {
+        self.bump_generation();
+        let cstr = CString::new(command.into())?;
+        let captured = Rc::new(RefCell::new(CapturedOutput::default()));
+        let callbacks: IDebugOutputCallbacks = OutputCapture { captured: captured.clone() }.into();
+
+        unsafe {
+            // The client may have no callbacks installed, in which case `None` is restored
+            let previous = self.client.GetOutputCallbacks().ok();
+            let mask = self.client.GetOutputMask()?;
+
+            self.client.SetOutputCallbacks(&callbacks)?;
+            let result = self
+                .client
+                .SetOutputMask(mask | DEBUG_OUTPUT_NORMAL | DEBUG_OUTPUT_ERROR | DEBUG_OUTPUT_WARNING | DEBUG_OUTPUT_VERBOSE)
+                .and_then(|_| {
+                    self.control
+                        .Execute(DEBUG_OUTCTL_THIS_CLIENT, PCSTR(cstr.as_ptr().cast()), DEBUG_EXECUTE_DEFAULT)
+                });
+
+            // Restore both even if one fails, callbacks first so the client never keeps
+            // pointing at the capture once it is dropped, then report the first error
+            let callbacks_restored = self.client.SetOutputCallbacks(previous.as_ref());
+            let mask_restored = self.client.SetOutputMask(mask);
+            result.and(callbacks_restored).and(mask_restored)?;
+        }
+
+        Ok(captured.take())
+    }
+
+    /// Registers a handler notified of the events raised by the target.
+    ///
+    /// The handler replaces any callbacks previously installed on this client and stays
+    /// alive until it is replaced or [`Dbg::clear_event_handler`] is called. Events are
+    /// delivered while the engine waits for them, e.g. during `WaitForEvent`.
+    ///
+    /// # Arguments
+    ///
+    /// * `handler` - The handler receiving the events.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// struct Tracer;
+    ///
+    /// impl EventHandler for Tracer {
+    ///     fn exception(&mut self, event: &ExceptionEvent) -> ExecutionStatus {
+    ///         println!("exception {:#x} at {:#x}", event.code, event.address);
+    ///         ExecutionStatus::Break
+    ///     }
+    /// }
+    ///
+    /// dbg.set_event_handler(Tracer)?;
+    /// ```
+    pub fn set_event_handler<H>(&self, handler: H) -> Result<(), DbgError>
+    where
+        H: EventHandler + 'static,
+    {
+        let callbacks: IDebugEventCallbacks = EventBridge {
+            handler: RefCell::new(Box::new(handler)),
+        }
+        .into();
+
+        unsafe { self.client.SetEventCallbacks(&callbacks)? };
+        self.event_callbacks.replace(Some(callbacks));
+
+        Ok(())
+    }
+
+    /// Unregisters the handler installed with [`Dbg::set_event_handler`].
+    pub fn clear_event_handler(&self) -> Result<(), DbgError> {
+        unsafe { self.client.SetEventCallbacks(None)? };
+        self.event_callbacks.replace(None);
+
+        Ok(())
+    }
+
+    /// Retrieves the current execution status of the target.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// if dbg.execution_status()? == ExecutionStatus::Break {
+    ///     dprintln!(dbg, "The target is stopped");
+    /// }
+    /// ```
+    pub fn execution_status(&self) -> Result<ExecutionStatus, DbgError> {
+        // The upper bits carry `DEBUG_STATUS_INSIDE_WAIT` and similar flags
+        let status = unsafe { self.control.GetExecutionStatus()? } & DEBUG_STATUS_MASK;
+        ExecutionStatus::try_from(status).map_err(|_| DbgError::DbgGeneralError("Unknown execution status"))
+    }
+
+    /// Sets the execution status of the target, e.g. to resume or single-step it.
+    ///
+    /// The target only runs once the engine waits for events, see [`Dbg::wait_for_event`].
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The new execution status.
+    pub fn set_execution_status(&self, status: ExecutionStatus) -> Result<(), DbgError> {
+        self.bump_generation();
+        unsafe { Ok(self.control.SetExecutionStatus(status as u32)?) }
+    }
+
+    /// Resumes the target, like `g`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// dbg.go()?;
+    /// dbg.wait_for_event(None)?;
+    /// ```
+    #[inline(always)]
+    pub fn go(&self) -> Result<(), DbgError> {
+        self.set_execution_status(ExecutionStatus::Go)
+    }
+
+    /// Executes a single instruction, stepping into calls, like `t`.
+    #[inline(always)]
+    pub fn step_into(&self) -> Result<(), DbgError> {
+        self.set_execution_status(ExecutionStatus::StepInto)
+    }
+
+    /// Executes a single instruction, stepping over calls, like `p`.
+    #[inline(always)]
+    pub fn step_over(&self) -> Result<(), DbgError> {
+        self.set_execution_status(ExecutionStatus::StepOver)
+    }
+
+    /// Runs until the current function returns, like `gu`.
+    ///
+    /// The engine has no execution status for this, so the `gu` command is issued instead.
+    pub fn step_out(&self) -> Result<(), DbgError> {
+        self.exec("gu")
+    }
+
+    /// Requests the engine to break into the target.
+    ///
+    /// [`Dbg`] cannot leave its thread, so use [`Dbg::interrupt_handle`] to interrupt
+    /// a [`Dbg::wait_for_event`] call blocked on another thread.
+    pub fn interrupt(&self) -> Result<(), DbgError> {
+        unsafe { Ok(self.control.SetInterrupt(DEBUG_INTERRUPT_ACTIVE)?) }
+    }
+
+    /// Returns a handle that can interrupt the target from another thread.
+    ///
+    /// The handle owns a separate client, so it stays valid while this instance is
+    /// blocked in [`Dbg::wait_for_event`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let handle = dbg.interrupt_handle()?;
+    /// std::thread::spawn(move || {
+    ///     std::thread::sleep(Duration::from_secs(5));
+    ///     handle.interrupt()
+    /// });
+    ///
+    /// dbg.go()?;
+    /// dbg.wait_for_event(None)?;
+    /// ```
+    pub fn interrupt_handle(&self) -> Result<DbgInterrupt, DbgError> {
+        let client = unsafe { self.client.CreateClient()? };
+        Ok(DbgInterrupt { control: client.cast()? })
+    }
+
+    /// Lets the target run until it raises an event that breaks into the debugger.
+    ///
+    /// Returns `true` once the target has stopped, or `false` if `timeout` elapsed while it
+    /// was still running. This cannot be called from inside an extension command, where
+    /// the engine is already waiting for events.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - How long to wait, or `None` to wait indefinitely.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// dbg.step_over()?;
+    /// if dbg.wait_for_event(Some(Duration::from_secs(5)))? {
+    ///     dprintln!(dbg, "Stopped at {:#x}", dbg.eval::<u64>("@$ip")?);
+    /// }
+    /// ```
+    pub fn wait_for_event(&self, timeout: Option<Duration>) -> Result<bool, DbgError> {
+        // `u32::MAX` is `INFINITE`, so finite timeouts are clamped just below it
+        let timeout = timeout.map_or(u32::MAX, |t| t.as_millis().min(u128::from(u32::MAX - 1)) as u32);
+        let result = unsafe { self.control.WaitForEvent(DEBUG_WAIT_DEFAULT, timeout) };
+        self.bump_generation();
+        result?;
+
+        // A timeout is reported as `S_FALSE`, which is not an error, so check whether the target stopped
+        Ok(matches!(self.execution_status()?, ExecutionStatus::Break | ExecutionStatus::NoDebuggee))
+    }
+
+    /// Returns a counter that changes whenever the target resumes, switches context or has its memory written.
+    ///
+    /// Changes made through this instance are always seen. Changes made by the user or other
+    /// clients are reported by the engine to a dedicated client installed on the first call.
+    pub fn generation(&self) -> u64 {
+        self.state_watcher.get_or_init(|| {
+            // Without the watcher, only changes made through this instance are tracked
+            let client = unsafe { self.client.CreateClient() }.ok()?;
+            let callbacks: IDebugEventCallbacks = StateWatcher {
+                generation: self.generation.clone(),
+            }
+            .into();
+
+            unsafe { client.SetEventCallbacks(&callbacks) }.ok()?;
+            Some((client, callbacks))
+        });
+
+        self.generation.get()
+    }
+
+    /// Marks every cached view of the target memory as stale.
+    fn bump_generation(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
+
+    /// Adds a code breakpoint at an address.
+    ///
+    /// The breakpoint is created disabled; call [`Breakpoint::enable`] once it is configured.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The address of the instruction to break on.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let bp = dbg.add_breakpoint(0x7FFF_FFFF_0000)?;
+    /// bp.set_one_shot(true)?;
+    /// bp.enable()?;
+    /// ```
+    pub fn add_breakpoint(&self, offset: u64) -> Result<Breakpoint, DbgError> {
+        self.create_breakpoint(DEBUG_BREAKPOINT_CODE, |bp| bp.set_offset(offset))
+    }
+
+    /// Adds a code breakpoint whose location is given by an expression.
+    ///
+    /// Unresolved symbols are deferred until the module defining them is loaded, like `bu`.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The location expression, e.g. `nt!NtCreateFile`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let bp = dbg.add_breakpoint_expr("ntdll!NtAllocateVirtualMemory")?;
+    /// bp.set_command("r rcx, rdx; gc")?;
+    /// bp.enable()?;
+    /// ```
+    pub fn add_breakpoint_expr<S>(&self, expr: S) -> Result<Breakpoint, DbgError>
+    where
+        S: Into<String>,
+    {
+        let expr = expr.into();
+        self.create_breakpoint(DEBUG_BREAKPOINT_CODE, |bp| bp.set_offset_expression(&expr))
+    }
+
+    /// Adds a processor (data) breakpoint watching a range of memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The address of the watched memory.
+    /// * `size` - The number of bytes watched: 1, 2, 4 or 8.
+    /// * `access` - The access that triggers the breakpoint.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let bp = dbg.add_data_breakpoint(0x7FFF_FFFF_0000, 8, BreakpointAccess::Write)?;
+    /// bp.enable()?;
+    /// ```
+    pub fn add_data_breakpoint(&self, offset: u64, size: u32, access: BreakpointAccess) -> Result<Breakpoint, DbgError> {
+        self.create_breakpoint(DEBUG_BREAKPOINT_DATA, |bp| {
+            bp.set_data_parameters(size, access)?;
+            bp.set_offset(offset)
+        })
+    }
+
+    /// Retrieves every breakpoint known to the engine.
+    pub fn breakpoints(&self) -> Result<Vec<Breakpoint>, DbgError> {
+        let count = unsafe { self.control.GetNumberBreakpoints()? };
+        (0..count)
+            .map(|index| {
+                let bp = unsafe { self.control4.GetBreakpointByIndex2(index)? };
+                Ok(Breakpoint::new(bp))
+            })
+            .collect()
+    }
+
+    /// Retrieves a breakpoint by its engine identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The breakpoint identifier, as shown by `bl`.
+    pub fn breakpoint_by_id(&self, id: u32) -> Result<Breakpoint, DbgError> {
+        let bp = unsafe { self.control4.GetBreakpointById2(id) }.map_err(|_| DbgError::BreakpointNotFound(id))?;
+        Ok(Breakpoint::new(bp))
+    }
+
+    /// Removes a breakpoint from the engine.
+    ///
+    /// # Arguments
+    ///
+    /// * `bp` - The breakpoint to remove.
+    pub fn remove_breakpoint(&self, bp: Breakpoint) -> Result<(), DbgError> {
+        unsafe { Ok(self.control4.RemoveBreakpoint2(&bp.breakpoint)?) }
+    }
+
+    /// Adds a breakpoint of the given `DEBUG_BREAKPOINT_*` type and configures it,
+    /// removing it again if the configuration fails.
+    fn create_breakpoint(&self, kind: u32, configure: impl FnOnce(&Breakpoint) -> Result<(), DbgError>) -> Result<Breakpoint, DbgError> {
+        let bp = Breakpoint::new(unsafe { self.control4.AddBreakpoint2(kind, DEBUG_ANY_ID)? });
+
+        if let Err(err) = configure(&bp) {
+            unsafe { self.control4.RemoveBreakpoint2(&bp.breakpoint)? };
+            return Err(err);
+        }
+
+        Ok(bp)
+    }
+
     /// Sends a message to the debugger output with a specific mask.
     ///
     /// # Arguments
@@ -185,6 +586,25 @@ impl Dbg {
         unsafe { Ok(self.control.GetNumberProcessors()?) }
     }
 
+    /// Retrieves the width of pointers in the current process, honouring the effective processor.
+    ///
+    /// A WOW64 process debugged with the x86 effective processor (`.effmach x86`) has 32-bit pointers.
+    pub fn pointer_width(&self) -> Result<PointerWidth, DbgError> {
+        let machine = unsafe { self.control.GetEffectiveProcessorType()? };
+        match machine {
+            IMAGE_FILE_MACHINE_I386 | IMAGE_FILE_MACHINE_ARM | IMAGE_FILE_MACHINE_ARMNT | IMAGE_FILE_MACHINE_THUMB => Ok(PointerWidth::Bits32),
+            IMAGE_FILE_MACHINE_AMD64 | IMAGE_FILE_MACHINE_ARM64 => Ok(PointerWidth::Bits64),
+            _ => {
+                // Let the engine decide for other processors. It answers with `S_OK` or `S_FALSE`,
+                // which the generated wrapper would both turn into `Ok`
+                let hr = unsafe { (Interface::vtable(&self.control).IsPointer64Bit)(Interface::as_raw(&self.control)) };
+                hr.ok()?;
+
+                Ok(if hr == HRESULT(0) { PointerWidth::Bits64 } else { PointerWidth::Bits32 })
+            }
+        }
+    }
+
     /// Retrieves the type of the debugged system.
     pub fn debug_type(&self) -> Result<(u32, u32), DbgError> {
         let (mut class, mut qualifier) = (0, 0);
@@ -208,14 +628,8 @@ impl Dbg {
     where
         T: DebugValue,
     {
-        let cstr = CString::new(expr)?;
-        let mut value = DEBUG_VALUE::default();
-        unsafe {
-            self.control
-                .Evaluate(PCSTR(cstr.as_ptr().cast()), T::VALUE_TYPE, &mut value, None)?;
-        }
-
-        Ok(T::from_debug_value(&value))
+        let value = Commands::evaluate(self, expr, T::value_type())?;
+        Ok(T::from_value(&value))
     }
 
     /// Retrieves the address of a symbol by its name.
@@ -239,6 +653,9 @@ impl Dbg {
 
     /// Resolves a symbol name from a given address.
     ///
+    /// The name is that of the closest symbol at or below the address, without the
+    /// displacement. Use [`Dbg::symbol_at`] to also get the distance to the symbol.
+    ///
     /// # Arguments
     ///
     /// * `addr` - The address to resolve to a symbol name.
@@ -249,23 +666,93 @@ impl Dbg {
     /// dbg.get_symbol_name(0x7FFF_FFFF_0000)?;
     /// ```
     pub fn get_symbol_name(&self, addr: u64) -> Result<String, DbgError> {
-        // Allocate a buffer to hold the symbol name (initial size: 1024 bytes)
-        let mut buffer = vec![0u8; 1024];
-        let mut size = 0u32;
-        unsafe {
-            self.symbols.GetNameByOffset(addr, Some(&mut buffer), Some(&mut size), None)?;
-        }
+        self.symbol_at(addr)?
+            .map(|symbol| symbol.qualified_name())
+            .ok_or_else(|| DbgError::SymbolNotFound(format!("{addr:#x}")))
+    }
 
-        // Check if the size of the symbol name is zero (indicating failure)
-        if size == 0 {
-            return Err(DbgError::InvalidSize(size as usize));
+    /// Finds the symbol containing an address, or `None` if no symbol matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to resolve.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// if let Some(symbol) = dbg.symbol_at(rip)? {
+    ///     dprintln!(dbg, "{} in {} at +{:#x}", symbol.name, symbol.module, symbol.displacement);
+    /// }
+    /// ```
+    pub fn symbol_at(&self, addr: u64) -> Result<Option<SymbolAt>, DbgError> {
+        self.name_by_offset(addr, None)
+    }
+
+    /// Enumerates the symbols matching a wildcard pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern, using `*` and `?` wildcards, e.g. `nt!Psp*Notify*`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// for (name, addr) in dbg.symbols_matching("nt!Psp*Notify*")? {
+    ///     dprintln!(dbg, "{addr:#x} {name}");
+    /// }
+    /// ```
+    pub fn symbols_matching<S>(&self, pattern: S) -> Result<SymbolMatches<'_>, DbgError>
+    where
+        S: Into<String>,
+    {
+        let cstr = CString::new(pattern.into())?;
+        let handle = unsafe { self.symbols.StartSymbolMatch(PCSTR(cstr.as_ptr().cast()))? };
+        Ok(SymbolMatches::new(self, handle))
+    }
+
+    /// Finds the symbol preceding the one containing an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to start from.
+    pub fn previous_symbol(&self, addr: u64) -> Result<Option<SymbolAt>, DbgError> {
+        self.name_by_offset(addr, Some(-1))
+    }
+
+    /// Finds the symbol following the one containing an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to start from.
+    pub fn next_symbol(&self, addr: u64) -> Result<Option<SymbolAt>, DbgError> {
+        self.name_by_offset(addr, Some(1))
+    }
+
+    /// Calls `GetNameByOffset`, or `GetNearNameByOffset` when `delta` is given, with a
+    /// buffer sized for the name.
+    fn name_by_offset(&self, addr: u64, delta: Option<i32>) -> Result<Option<SymbolAt>, DbgError> {
+        let query = |buffer: Option<&mut [u8]>, size: &mut u32, displacement: &mut u64| unsafe {
+            match delta {
+                None => self.symbols.GetNameByOffset(addr, buffer, Some(size), Some(displacement)),
+                Some(delta) => self
+                    .symbols
+                    .GetNearNameByOffset(addr, delta, buffer, Some(size), Some(displacement)),
+            }
+        };
+
+        // Ask for the size first so that long C++ names are not cut
+        let (mut size, mut displacement) = (0u32, 0u64);
+        match query(None, &mut size, &mut displacement) {
+            Err(err) if err.code() == E_FAIL || err.code() == E_NOINTERFACE => return Ok(None),
+            result => result?,
         }
 
-        // Resize the buffer to match the actual size of the symbol name
-        // Subtract 1 to exclude the null terminator added by the API
-        buffer.resize((size - 1) as usize, 0);
+        let mut buffer = vec![0u8; size as usize];
+        query(Some(&mut buffer), &mut size, &mut displacement)?;
 
-        Ok(String::from_utf8_lossy(&buffer).to_string())
+        // Drop the null terminator added by the API
+        buffer.truncate(buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len()));
+        Ok(Some(SymbolAt::new(&String::from_utf8_lossy(&buffer), addr, displacement)))
     }
 
     /// Removes a synthetic module, either by its base address or by its name.
@@ -310,6 +797,8 @@ impl Dbg {
 
     /// Reads a range of virtual memory into a buffer.
     ///
+    /// Fails with [`DbgError::InvalidAddress`] if the memory at `vaddr` cannot be read.
+    ///
     /// # Arguments
     ///
     /// * `vaddr` - The starting virtual address to read from.
@@ -323,22 +812,525 @@ impl Dbg {
     /// ```
     pub fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
         let mut bytes_read = 0;
-        unsafe {
+        let result = unsafe {
             self.dataspaces
-                .ReadVirtual(vaddr, buffer.as_mut_ptr().cast(), buffer.len() as u32, Some(&mut bytes_read))?;
-        }
+                .ReadVirtual(vaddr, buffer.as_mut_ptr().cast(), buffer.len() as u32, Some(&mut bytes_read))
+        };
 
-        Ok(bytes_read as usize)
+        // Report unreadable memory like the other backends, so callers can tell it from failures
+        match result {
+            Ok(()) => Ok(bytes_read as usize),
+            Err(err) if matches!(err.code(), E_READ_FAULT | E_PARTIAL_COPY) => Err(DbgError::InvalidAddress(vaddr)),
+            Err(err) => Err(err.into()),
+        }
     }
 
-    /// Adds a synthetic module to the debugger's symbol table.
+    /// Writes a buffer to virtual memory.
+    ///
+    /// Fails with [`DbgError::PartialWrite`] if only part of the buffer could be written.
     ///
     /// # Arguments
     ///
-    /// * `expr` - The expression used to compute the base address of the synthetic module.
-    /// * `size` - The size of the synthetic module in bytes.
-    /// * `name` - The name of the synthetic module. It must implement `Into<String>`.
-    /// * `path` - The file path of the synthetic module. It must be a valid `PathBuf`.
+    /// * `vaddr` - The starting virtual address to write to.
+    /// * `buffer` - The bytes to write.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// dbg.write_vaddr(0x7FFF_FFFF_0000, &[0x90, 0x90])?;
+    /// ```
+    pub fn write_vaddr(&self, vaddr: u64, buffer: &[u8]) -> Result<(), DbgError> {
+        self.bump_generation();
+        let mut bytes_written = 0;
+        unsafe {
+            self.dataspaces
+                .WriteVirtual(vaddr, buffer.as_ptr().cast(), buffer.len() as u32, Some(&mut bytes_written))?;
+        }
+
+        check_written(buffer.len(), bytes_written)
+    }
+
+    /// Writes a plain-old-data value to a given virtual memory address.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual memory address to write to.
+    /// * `value` - The value to write.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// dbg.write_type_vaddr(0x7FFF_FFFF_0000, 0xDEADBEEFu32)?;
+    /// ```
+    pub fn write_type_vaddr<T: Pod>(&self, vaddr: u64, value: T) -> Result<(), DbgError> {
+        let size = size_of::<T>();
+        if size == 0 {
+            return Err(DbgError::InvalidSize(size));
+        }
+
+        self.write_vaddr(vaddr, as_bytes(&[value]))
+    }
+
+    /// Fills a range of virtual memory by repeating a pattern.
+    ///
+    /// The last copy of the pattern is truncated if `size` is not a multiple of its length.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual address of the range.
+    /// * `size` - The number of bytes to fill.
+    /// * `pattern` - The bytes to repeat.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// dbg.fill_vaddr(0x7FFF_FFFF_0000, 0x100, &[0xCC])?;
+    /// ```
+    pub fn fill_vaddr(&self, vaddr: u64, size: u32, pattern: &[u8]) -> Result<(), DbgError> {
+        if pattern.is_empty() {
+            return Err(DbgError::InvalidSize(0));
+        }
+
+        self.bump_generation();
+        let mut filled = 0;
+        unsafe {
+            self.dataspaces
+                .FillVirtual(vaddr, size, pattern.as_ptr().cast(), pattern.len() as u32, Some(&mut filled))?;
+        }
+
+        check_written(size as usize, filled)
+    }
+
+    /// Reads from any memory space of the target into a buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - The memory space to read from.
+    /// * `addr` - The address inside that space, or the MSR number for [`MemorySpace::Msr`].
+    /// * `buffer` - A mutable slice where the read bytes will be stored.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut buffer = [0u8; 0x1000];
+    /// let read = dbg.read(MemorySpace::Physical(PhysicalCaching::Default), 0x1AD000, &mut buffer)?;
+    /// ```
+    pub fn read(&self, space: MemorySpace, addr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        let (data, size) = (buffer.as_mut_ptr().cast(), buffer.len() as u32);
+        let mut bytes_read = 0;
+        unsafe {
+            match space {
+                MemorySpace::Virtual => self.dataspaces.ReadVirtual(addr, data, size, Some(&mut bytes_read))?,
+                MemorySpace::Physical(caching) => self
+                    .dataspaces
+                    .ReadPhysical2(addr, caching as u32, data, size, Some(&mut bytes_read))?,
+                MemorySpace::Control { processor } => self
+                    .dataspaces
+                    .ReadControl(processor, addr, data, size, Some(&mut bytes_read))?,
+                MemorySpace::Io {
+                    interface_type,
+                    bus_number,
+                    address_space,
+                } => self
+                    .dataspaces
+                    .ReadIo(interface_type, bus_number, address_space, addr, data, size, Some(&mut bytes_read))?,
+                MemorySpace::BusData {
+                    bus_data_type,
+                    bus_number,
+                    slot_number,
+                } => {
+                    let offset = u32::try_from(addr).map_err(|_| DbgError::InvalidAddress(addr))?;
+                    self.dataspaces
+                        .ReadBusData(bus_data_type, bus_number, slot_number, offset, data, size, Some(&mut bytes_read))?
+                }
+                MemorySpace::Msr => {
+                    let msr = msr_number(addr, buffer.len())?;
+                    buffer.copy_from_slice(&self.dataspaces.ReadMsr(msr)?.to_le_bytes());
+                    bytes_read = size;
+                }
+            }
+        }
+
+        Ok(bytes_read as usize)
+    }
+
+    /// Writes a buffer to any memory space of the target.
+    ///
+    /// Fails with [`DbgError::PartialWrite`] if only part of the buffer could be written.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - The memory space to write to.
+    /// * `addr` - The address inside that space, or the MSR number for [`MemorySpace::Msr`].
+    /// * `buffer` - The bytes to write.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// dbg.write(MemorySpace::Io { interface_type: 1, bus_number: 0, address_space: 1 }, 0x80, &[0x42])?;
+    /// ```
+    pub fn write(&self, space: MemorySpace, addr: u64, buffer: &[u8]) -> Result<(), DbgError> {
+        self.bump_generation();
+        let (data, size) = (buffer.as_ptr().cast(), buffer.len() as u32);
+        let mut bytes_written = 0;
+        unsafe {
+            match space {
+                MemorySpace::Virtual => self.dataspaces.WriteVirtual(addr, data, size, Some(&mut bytes_written))?,
+                MemorySpace::Physical(caching) => self
+                    .dataspaces
+                    .WritePhysical2(addr, caching as u32, data, size, Some(&mut bytes_written))?,
+                MemorySpace::Control { processor } => self
+                    .dataspaces
+                    .WriteControl(processor, addr, data, size, Some(&mut bytes_written))?,
+                MemorySpace::Io {
+                    interface_type,
+                    bus_number,
+                    address_space,
+                } => self
+                    .dataspaces
+                    .WriteIo(interface_type, bus_number, address_space, addr, data, size, Some(&mut bytes_written))?,
+                MemorySpace::BusData {
+                    bus_data_type,
+                    bus_number,
+                    slot_number,
+                } => {
+                    let offset = u32::try_from(addr).map_err(|_| DbgError::InvalidAddress(addr))?;
+                    self.dataspaces
+                        .WriteBusData(bus_data_type, bus_number, slot_number, offset, data, size, Some(&mut bytes_written))?
+                }
+                MemorySpace::Msr => {
+                    let msr = msr_number(addr, buffer.len())?;
+                    let mut value = [0u8; 8];
+                    value.copy_from_slice(buffer);
+                    self.dataspaces.WriteMsr(msr, u64::from_le_bytes(value))?;
+                    bytes_written = size;
+                }
+            }
+        }
+
+        check_written(buffer.len(), bytes_written)
+    }
+
+    /// Returns an iterator over the virtual memory regions of the current process.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// for region in dbg.regions() {
+    ///     let region = region?;
+    ///     if region.protection == Some(Protection::ExecuteReadWrite) {
+    ///         dprintln!(dbg, "RWX {:#x}-{:#x}", region.base, region.end());
+    ///     }
+    /// }
+    /// ```
+    pub fn regions(&self) -> Regions<'_> {
+        Regions::new(self)
+    }
+
+    /// Searches the image of a loaded module for a byte signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - The module name, e.g. `nt` or `ntdll`.
+    /// * `pattern` - The signature to search for.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let pattern = Pattern::from_ida("48 8B 0D ?? ?? ?? ?? E8")?;
+    /// let matches = dbg.scan_module("nt", &pattern)?;
+    /// ```
+    pub fn scan_module<S>(&self, module: S, pattern: &Pattern) -> Result<Vec<u64>, DbgError>
+    where
+        S: Into<String>,
+    {
+        let params = self.module_parameters(module.into())?;
+        pattern.scan(self, params.Base, u64::from(params.Size))
+    }
+
+    /// Searches every committed, readable region of the current process for a byte signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The signature to search for.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let pattern = Pattern::from_mask(b"MZ\x90\x00", "xxxx")?;
+    /// for addr in dbg.scan_regions(&pattern)? {
+    ///     dprintln!(dbg, "PE header at {addr:#x}");
+    /// }
+    /// ```
+    pub fn scan_regions(&self, pattern: &Pattern) -> Result<Vec<u64>, DbgError> {
+        let mut matches = Vec::new();
+        for region in self.regions() {
+            let region = region?;
+            if !region.is_readable() {
+                continue;
+            }
+
+            matches.extend(pattern.scan(self, region.base, region.size)?);
+        }
+
+        Ok(matches)
+    }
+
+    /// Captures the sections of a loaded module into a [`Snapshot`].
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - The module name, e.g. `nt` or `ntdll`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let before = dbg.snapshot_module("ntdll")?;
+    /// dbg.step_over()?;
+    /// for change in before.diff(&dbg.snapshot_module("ntdll")?) {
+    ///     dprintln!(dbg, "{:#x} {:?}", change.addr, change.symbol);
+    /// }
+    /// ```
+    pub fn snapshot_module<S>(&self, module: S) -> Result<Snapshot, DbgError>
+    where
+        S: Into<String>,
+    {
+        let name = module.into();
+        let params = self.module_parameters(name.clone())?;
+
+        let mut snapshot = Snapshot::new();
+        snapshot.capture_module(self, &name, params.Base)?;
+        Ok(snapshot)
+    }
+
+    /// Captures every committed, readable region of the current process into a [`Snapshot`].
+    pub fn snapshot_regions(&self) -> Result<Snapshot, DbgError> {
+        let mut snapshot = Snapshot::new();
+        snapshot.capture_regions(self, self.regions().collect::<Result<Vec<_>, _>>()?)?;
+        Ok(snapshot)
+    }
+
+    /// Returns the parameters of a loaded module found by name.
+    fn module_parameters(&self, module: String) -> Result<DEBUG_MODULE_PARAMETERS, DbgError> {
+        let cstr = CString::new(module)?;
+        let mut base = 0;
+        let mut params = DEBUG_MODULE_PARAMETERS::default();
+        unsafe {
+            self.symbols
+                .GetModuleByModuleName(PCSTR(cstr.as_ptr().cast()), 0, None, Some(&mut base))?;
+            self.symbols.GetModuleParameters(1, Some(&base), 0, &mut params)?;
+        }
+
+        Ok(params)
+    }
+
+    /// Resolves a type name to the base address of its module and the engine's type identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The type name, e.g. `nt!_EPROCESS`.
+    pub fn type_id(&self, name: &str) -> Result<(u64, u32), DbgError> {
+        let not_found = || DbgError::SymbolNotFound(name.to_string());
+        match name.split_once('!') {
+            Some((module, type_name)) => {
+                let module = self.module_parameters(module.to_string()).map_err(|_| not_found())?.Base;
+                let cstr = CString::new(type_name)?;
+                let type_id = unsafe { self.symbols.GetTypeId(module, PCSTR(cstr.as_ptr().cast())) }.map_err(|_| not_found())?;
+                Ok((module, type_id))
+            }
+            None => {
+                let cstr = CString::new(name)?;
+                let (mut module, mut type_id) = (0, 0);
+                unsafe {
+                    self.symbols
+                        .GetSymbolTypeId(PCSTR(cstr.as_ptr().cast()), &mut type_id, Some(&mut module))
+                        .map_err(|_| not_found())?
+                };
+                Ok((module, type_id))
+            }
+        }
+    }
+
+    /// Returns the size of a type in bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The type name, e.g. `nt!_EPROCESS`.
+    pub fn type_size(&self, name: &str) -> Result<u32, DbgError> {
+        let (module, type_id) = self.type_id(name)?;
+        unsafe { Ok(self.symbols.GetTypeSize(module, type_id)?) }
+    }
+
+    /// Returns the offset of a field inside a type.
+    ///
+    /// Fails with [`DbgError::FieldNotFound`] if the type has no such field, and with the
+    /// engine's error for any other failure, such as missing symbols.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - The type name, e.g. `nt!_EPROCESS`.
+    /// * `field` - The field name, e.g. `UniqueProcessId`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let offset = dbg.field_offset("nt!_EPROCESS", "ActiveProcessLinks")?;
+    /// ```
+    pub fn field_offset(&self, type_name: &str, field: &str) -> Result<u32, DbgError> {
+        let (module, type_id) = self.type_id(type_name)?;
+        let cstr = CString::new(field)?;
+        match unsafe { self.symbols.GetFieldOffset(module, type_id, PCSTR(cstr.as_ptr().cast())) } {
+            // `E_NOINTERFACE` is how the engine reports a type without such a field
+            Err(err) if err.code() == E_NOINTERFACE => Err(DbgError::FieldNotFound {
+                type_name: type_name.to_string(),
+                field: field.to_string(),
+            }),
+            result => Ok(result?),
+        }
+    }
+
+    /// Resolves the layout of a type and its direct fields.
+    ///
+    /// Fails with the engine's error if a field cannot be enumerated, rather than returning
+    /// a partial field list.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The type name, e.g. `nt!_EPROCESS`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let eprocess = dbg.type_info("nt!_EPROCESS")?;
+    /// for field in &eprocess.fields {
+    ///     dprintln!(dbg, "+{:#05x} {} ({} bytes)", field.offset, field.name, field.size);
+    /// }
+    /// ```
+    pub fn type_info(&self, name: &str) -> Result<TypeInfo, DbgError> {
+        let (module, type_id) = self.type_id(name)?;
+        let size = unsafe { self.symbols.GetTypeSize(module, type_id)? };
+
+        let mut fields = Vec::new();
+        while let Some(field) = self.field_name(module, type_id, fields.len() as u32)? {
+            let cstr = CString::new(field.as_str())?;
+            let (mut field_type, mut offset) = (0, 0);
+            unsafe {
+                self.symbols
+                    .GetFieldTypeAndOffset(module, type_id, PCSTR(cstr.as_ptr().cast()), Some(&mut field_type), Some(&mut offset))?
+            };
+
+            fields.push(FieldInfo {
+                name: field,
+                offset,
+                type_id: field_type,
+                size: unsafe { self.symbols.GetTypeSize(module, field_type)? },
+            });
+        }
+
+        Ok(TypeInfo {
+            name: name.to_string(),
+            module,
+            type_id,
+            size,
+            fields,
+        })
+    }
+
+    /// Returns the name of the field at `index`, or `None` past the last field.
+    fn field_name(&self, module: u64, type_id: u32, index: u32) -> Result<Option<String>, DbgError> {
+        // The engine signals the end of the field list by failing on the next index, any
+        // other failure is a real error
+        let mut size = 0u32;
+        match unsafe { self.symbols.GetFieldName(module, type_id, index, None, Some(&mut size)) } {
+            Err(err) if err.code() == E_INVALIDARG || err.code() == E_NOINTERFACE => return Ok(None),
+            result => result?,
+        }
+
+        let mut buffer = vec![0u8; size as usize];
+        unsafe { self.symbols.GetFieldName(module, type_id, index, Some(&mut buffer), None)? };
+
+        buffer.truncate(buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len()));
+        Ok(Some(String::from_utf8_lossy(&buffer).into_owned()))
+    }
+
+    /// Returns the modules currently loaded in the target.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// for module in dbg.modules()? {
+    ///     dprintln!(dbg, "{:#x} {} ({:?})", module.base, module.module_name, module.symbol_type);
+    /// }
+    /// ```
+    pub fn modules(&self) -> Result<Vec<ModuleInfo>, DbgError> {
+        let (loaded, _) = self.number_modules()?;
+        self.module_infos(0, loaded)
+    }
+
+    /// Returns the modules the engine has seen unloaded from the target.
+    pub fn unloaded_modules(&self) -> Result<Vec<ModuleInfo>, DbgError> {
+        let (loaded, unloaded) = self.number_modules()?;
+        self.module_infos(loaded, unloaded)
+    }
+
+    /// Returns the number of loaded and unloaded modules.
+    fn number_modules(&self) -> Result<(u32, u32), DbgError> {
+        let (mut loaded, mut unloaded) = (0, 0);
+        unsafe { self.symbols.GetNumberModules(&mut loaded, &mut unloaded)? };
+        Ok((loaded, unloaded))
+    }
+
+    /// Describes `count` modules starting at index `start`.
+    fn module_infos(&self, start: u32, count: u32) -> Result<Vec<ModuleInfo>, DbgError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut params = vec![DEBUG_MODULE_PARAMETERS::default(); count as usize];
+        unsafe { self.symbols.GetModuleParameters(count, None, start, params.as_mut_ptr())? };
+
+        // A name the engine cannot report, such as the path of an unloaded module, is left
+        // empty rather than failing the whole listing
+        let modules = params
+            .iter()
+            .zip(start..)
+            .map(|(params, index)| ModuleInfo {
+                base: params.Base,
+                size: params.Size,
+                module_name: self.module_name(DEBUG_MODNAME_MODULE, index).unwrap_or_default(),
+                image_name: self.module_name(DEBUG_MODNAME_IMAGE, index).unwrap_or_default(),
+                loaded_image_name: self.module_name(DEBUG_MODNAME_LOADED_IMAGE, index).unwrap_or_default(),
+                timestamp: params.TimeDateStamp,
+                checksum: params.Checksum,
+                symbol_type: SymbolType::try_from(params.SymbolType).unwrap_or_default(),
+                flags: params.Flags,
+            })
+            .collect();
+
+        Ok(modules)
+    }
+
+    /// Returns one of the names of the module at `index`.
+    fn module_name(&self, which: u32, index: u32) -> Result<String, DbgError> {
+        let mut size = 0u32;
+        unsafe { self.symbols.GetModuleNameString(which, index, 0, None, Some(&mut size))? };
+
+        let mut buffer = vec![0u8; size as usize];
+        unsafe { self.symbols.GetModuleNameString(which, index, 0, Some(&mut buffer), None)? };
+
+        // Drop the null terminator added by the API
+        buffer.truncate(buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len()));
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+
+    /// Adds a synthetic module to the debugger's symbol table.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression used to compute the base address of the synthetic module.
+    /// * `size` - The size of the synthetic module in bytes.
+    /// * `name` - The name of the synthetic module. It must implement `Into<String>`.
+    /// * `path` - The file path of the synthetic module. It must be a valid `PathBuf`.
     pub fn add_synthetic_module<S>(&self, expr: S, size: u32, name: S, path: PathBuf) -> Result<(), DbgError>
     where
         S: Into<String>,
@@ -368,28 +1360,15 @@ impl Dbg {
         Ok(())
     }
 
-    /// Safely reads a value of a specified type from a given virtual memory address.
+    /// Safely reads a value of a plain-old-data type from a given virtual memory address.
+    ///
+    /// Fails with [`DbgError::PartialRead`] if only part of the value could be read.
     ///
     /// # Arguments
     ///
     /// * `vaddr` - The starting virtual memory address to read from.
-    pub fn read_type_vaddr<T: Copy>(&self, vaddr: u64) -> Result<T, DbgError> {
-        // Determine the size of the target type `T`
-        let size = size_of::<T>();
-        if size == 0 {
-            return Err(DbgError::InvalidSize(size));
-        }
-
-        // Prepare a buffer to hold the exact size of the target type
-        let mut buffer = vec![0u8; size].into_boxed_slice();
-
-        // Read the memory into the buffer
-        self.read_vaddr(vaddr, &mut buffer)?;
-
-        // SAFETY: `read_unaligned` ensures that we can handle unaligned memory safely
-        let value = unsafe { (buffer.as_ptr() as *const T).read_unaligned() };
-
-        Ok(value)
+    pub fn read_type_vaddr<T: Pod>(&self, vaddr: u64) -> Result<T, DbgError> {
+        Memory::read_type_vaddr(self, vaddr)
     }
 
     /// Reads the value of a specific Model-Specific Register (MSR).
@@ -435,6 +1414,69 @@ impl Dbg {
         Ok(String::from_utf8_lossy(&buffer).to_string())
     }
 
+    /// Reads a null-terminated narrow string of at most `max_len` bytes, reporting truncation.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the string.
+    /// * `max_len` - The maximum number of characters to keep.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let name = dbg.read_cstr_max(0x7FFF_FFFF_0000, 1024)?;
+    /// if name.truncated {
+    ///     dprintln!(dbg, "String longer than 1024 bytes");
+    /// }
+    /// ```
+    pub fn read_cstr_max(&self, addr: u64, max_len: usize) -> Result<TargetString, DbgError> {
+        // Room for one character past `max_len` and the terminator tells whether the string is longer
+        let len = max_len.checked_add(2).ok_or(DbgError::InvalidSize(max_len))?;
+        let bytes = u32::try_from(len).map_err(|_| DbgError::InvalidSize(max_len))?;
+
+        let mut buffer = vec![0u8; len];
+        let mut size = 0;
+        unsafe {
+            self.dataspaces
+                .ReadMultiByteStringVirtual(addr, bytes, Some(&mut buffer), Some(&mut size))?;
+        }
+
+        let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+        Ok(TargetString::from_narrow(&buffer[..len.min(max_len)], len > max_len))
+    }
+
+    /// Reads a null-terminated UTF-16 string of at most `max_len` characters, reporting truncation.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the string.
+    /// * `max_len` - The maximum number of UTF-16 code units to keep.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let path = dbg.read_wstr(0x7FFF_FFFF_0000, 260)?;
+    /// dprintln!(dbg, "{}{}", path.text, if path.truncated { "..." } else { "" });
+    /// ```
+    pub fn read_wstr(&self, addr: u64, max_len: usize) -> Result<TargetString, DbgError> {
+        // Room for one character past `max_len` and the terminator tells whether the string is longer
+        let len = max_len.checked_add(2).ok_or(DbgError::InvalidSize(max_len))?;
+        let bytes = len
+            .checked_mul(2)
+            .and_then(|bytes| u32::try_from(bytes).ok())
+            .ok_or(DbgError::InvalidSize(max_len))?;
+
+        let mut buffer = vec![0u16; len];
+        let mut size = 0;
+        unsafe {
+            self.dataspaces
+                .ReadUnicodeStringVirtualWide(addr, bytes, Some(&mut buffer), Some(&mut size))?;
+        }
+
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        Ok(TargetString::from_wide(&buffer[..len.min(max_len)], len > max_len))
+    }
+
     /// Retrieves the register indices corresponding to a provided list of names.
     ///
     /// # Arguments
@@ -463,7 +1505,7 @@ impl Dbg {
     /// # Arguments
     ///
     /// * `indices` - A slice of register indices (`&[u32]`) for which the values need to be fetched.
-    /// 
+    ///
     /// # Example
     ///
     /// ```rust,ignore
@@ -471,7 +1513,7 @@ impl Dbg {
     /// let values = dbg.reg_values(&indices)?; // Retrieve the values for the registers.
     /// ```
     pub fn reg_values(&self, indices: &[u32]) -> Result<Vec<DEBUG_VALUE>, DbgError> {
-        let mut values = Vec::with_capacity(indices.len());
+        let mut values = vec![DEBUG_VALUE::default(); indices.len()];
         unsafe {
             self.registers
                 .GetValues(indices.len() as u32, Some(indices.as_ptr()), 0, values.as_mut_ptr())?;
@@ -481,81 +1523,122 @@ impl Dbg {
     }
 }
 
-/// A trait to extract a value from a [`DEBUG_VALUE`].
-pub trait DebugValue: Sized {
-    /// The corresponding `DEBUG_VALUE_TYPE` for this type.
-    const VALUE_TYPE: u32;
+/// A handle sending break requests to the target, obtained through [`Dbg::interrupt_handle`].
+#[derive(Debug)]
+pub struct DbgInterrupt {
+    /// The control interface of a client dedicated to interrupting the target.
+    control: IDebugControl4,
+}
 
-    /// Extracts the value from a `DEBUG_VALUE`
-    ///
-    /// # Arguments
+// SAFETY: the handle only calls `SetInterrupt`, which the engine documents as callable from any
+// thread, and it owns its client so nothing else uses the interface concurrently
+unsafe impl Send for DbgInterrupt {}
+
+impl DbgInterrupt {
+    /// Asks the engine to break into the target.
     ///
-    /// * `val` - A reference to the `DEBUG_VALUE` to be converted.
-    fn from_debug_value(val: &DEBUG_VALUE) -> Self;
+    /// The pending [`Dbg::wait_for_event`] call returns once the target has stopped.
+    pub fn interrupt(&self) -> Result<(), DbgError> {
+        unsafe { Ok(self.control.SetInterrupt(DEBUG_INTERRUPT_ACTIVE)?) }
+    }
 }
 
-impl DebugValue for u64 {
-    /// Specifies that the value should be interpreted as a 64-bit integer.
-    const VALUE_TYPE: u32 = DEBUG_VALUE_INT64;
+/// Validates an MSR access, which always transfers a single 64-bit value.
+fn msr_number(addr: u64, len: usize) -> Result<u32, DbgError> {
+    if len != size_of::<u64>() {
+        return Err(DbgError::InvalidSize(len));
+    }
+
+    u32::try_from(addr).map_err(|_| DbgError::InvalidAddress(addr))
+}
 
-    /// Extracts a 64-bit integer [`u64`] from a `DEBUG_VALUE`.
-    fn from_debug_value(val: &DEBUG_VALUE) -> Self {
-        unsafe { val.Anonymous.Anonymous.I64 }
+/// Turns a short write into [`DbgError::PartialWrite`].
+fn check_written(requested: usize, written: u32) -> Result<(), DbgError> {
+    match written as usize {
+        written if written < requested => Err(DbgError::PartialWrite { requested, written }),
+        _ => Ok(()),
     }
 }
 
-impl DebugValue for u32 {
-    /// Specifies that the value should be interpreted as a 32-bit integer.
-    const VALUE_TYPE: u32 = DEBUG_VALUE_INT32;
+impl Memory for Dbg {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        Dbg::read_vaddr(self, vaddr, buffer)
+    }
 
-    /// Extracts a 32-bit integer ([`u32`] from a `DEBUG_VALUE`.
-    fn from_debug_value(val: &DEBUG_VALUE) -> Self {
-        unsafe { val.Anonymous.I32 }
+    fn read_cstr(&self, addr: u64) -> Result<String, DbgError> {
+        Dbg::read_cstr(self, addr)
+    }
+
+    fn read_cstr_max(&self, addr: u64, max_len: usize) -> Result<TargetString, DbgError> {
+        Dbg::read_cstr_max(self, addr, max_len)
+    }
+
+    fn read_wstr(&self, addr: u64, max_len: usize) -> Result<TargetString, DbgError> {
+        Dbg::read_wstr(self, addr, max_len)
+    }
+
+    fn generation(&self) -> u64 {
+        Dbg::generation(self)
     }
-}
 
-impl DebugValue for f64 {
-    /// Specifies that the value should be interpreted as a 64-bit floating-point number.
-    const VALUE_TYPE: u32 = DEBUG_VALUE_FLOAT64;
+    fn pointer_width(&self) -> Result<PointerWidth, DbgError> {
+        Dbg::pointer_width(self)
+    }
+}
 
-    /// Extracts a 64-bit floating-point value [`f64`] from a `DEBUG_VALUE`.
-    fn from_debug_value(val: &DEBUG_VALUE) -> Self {
-        unsafe { val.Anonymous.F64 }
+impl PhysicalMemory for Dbg {
+    fn read_paddr(&self, paddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        Dbg::read(self, MemorySpace::Physical(PhysicalCaching::Default), paddr, buffer)
     }
 }
 
-impl DebugValue for f32 {
-    /// Specifies that the value should be interpreted as a 32-bit floating-point number.
-    const VALUE_TYPE: u32 = DEBUG_VALUE_FLOAT32;
+impl Symbols for Dbg {
+    fn get_symbol_address(&self, name: &str) -> Result<u64, DbgError> {
+        Dbg::get_symbol_address(self, name)
+    }
 
-    /// Extracts a 32-bit floating-point value [`f32`] from a `DEBUG_VALUE`.
-    fn from_debug_value(val: &DEBUG_VALUE) -> Self {
-        unsafe { val.Anonymous.F32 }
+    fn get_symbol_name(&self, addr: u64) -> Result<String, DbgError> {
+        Dbg::get_symbol_name(self, addr)
     }
 }
 
-/// Represents either a module address or a module name.
-///
-/// This enum is used to pass arguments to methods like [`Dbg::remove_synthetic_module`].
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Module {
-    /// A module identified by its base address.
-    Address(u64),
+impl Types for Dbg {
+    fn type_info(&self, name: &str) -> Result<TypeInfo, DbgError> {
+        Dbg::type_info(self, name)
+    }
 
-    /// A module identified by its name.
-    Name(String),
+    fn field_offset(&self, type_name: &str, field: &str) -> Result<u32, DbgError> {
+        Dbg::field_offset(self, type_name, field)
+    }
 }
 
-impl From<u64> for Module {
-    /// Converts a [`u64`] into a [`Module::Address`].
-    fn from(addr: u64) -> Self {
-        Module::Address(addr)
+impl Registers for Dbg {
+    fn reg_indices(&self, names: &[&str]) -> Result<Vec<u32>, DbgError> {
+        Dbg::reg_indices(self, names)
+    }
+
+    fn reg_values(&self, indices: &[u32]) -> Result<Vec<Value>, DbgError> {
+        Ok(Dbg::reg_values(self, indices)?.iter().map(Value::from).collect())
     }
 }
 
-impl From<&str> for Module {
-    /// Converts a `&str` into a [`Module::Name`].
-    fn from(name: &str) -> Self {
-        Module::Name(name.to_string())
+impl Commands for Dbg {
+    fn exec(&self, command: &str) -> Result<(), DbgError> {
+        Dbg::exec(self, command)
+    }
+
+    fn evaluate(&self, expr: &str, value_type: ValueType) -> Result<Value, DbgError> {
+        let cstr = CString::new(expr)?;
+        let mut value = DEBUG_VALUE::default();
+        unsafe {
+            self.control
+                .Evaluate(PCSTR(cstr.as_ptr().cast()), value_type as u32, &mut value, None)?;
+        }
+
+        Ok(Value::from(&value))
+    }
+
+    fn eval<T: DebugValue>(&self, expr: &str) -> Result<T, DbgError> {
+        Dbg::eval(self, expr)
     }
 }
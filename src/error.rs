@@ -1,6 +1,7 @@
 //! The module defines error types used throughout the library.
 
 use thiserror::Error;
+use crate::paging::PageLevel;
 
 /// Represents errors that can occur during debugging or low-level operations involving
 /// Windows APIs and other system-level functionalities.
@@ -14,7 +15,125 @@ pub enum DbgError {
     #[error("Invalid size: {0}")]
     InvalidSize(usize),
 
+    /// Raised when memory at the given address cannot be accessed.
+    #[error("Invalid address: {0:#x}")]
+    InvalidAddress(u64),
+
+    /// Raised when fewer bytes than requested could be read from the target.
+    #[error("Partial read: {read} of {requested} bytes read")]
+    PartialRead {
+        /// The number of bytes the caller asked to read.
+        requested: usize,
+
+        /// The number of bytes actually read.
+        read: usize,
+    },
+
+    /// Raised when fewer bytes than requested could be written to the target.
+    #[error("Partial write: {written} of {requested} bytes written")]
+    PartialWrite {
+        /// The number of bytes the caller asked to write.
+        requested: usize,
+
+        /// The number of bytes actually written.
+        written: usize,
+    },
+
+    /// Raised when a page-table walk reaches an entry that is not present.
+    #[error("Page not present: {vaddr:#x} is not mapped at the {level:?} level")]
+    PageNotPresent {
+        /// The virtual address being translated.
+        vaddr: u64,
+
+        /// The level whose entry is not present.
+        level: PageLevel,
+    },
+
+    /// Raised when a dereference in a pointer chain cannot be read.
+    #[error("Pointer chain broken at step {step}: cannot read pointer at {addr:#x}: {source}")]
+    PointerChain {
+        /// The failing dereference, counting from 1 in the order the reads happen.
+        step: usize,
+
+        /// The address the pointer was read from.
+        addr: u64,
+
+        /// The error returned by the read.
+        source: Box<DbgError>,
+    },
+
+    /// Raised when a symbol cannot be resolved.
+    #[error("Symbol not found: {0}")]
+    SymbolNotFound(String),
+
+    /// Raised when a type has no field with the given name.
+    #[error("Field not found: {type_name} has no field {field}")]
+    FieldNotFound {
+        /// The type that was searched.
+        type_name: String,
+
+        /// The missing field.
+        field: String,
+    },
+
+    /// Raised when a Rust field does not have the size of the target field it is read from.
+    #[error("Field size mismatch: {type_name}.{field} is {expected} bytes, the Rust field is {actual}")]
+    FieldSizeMismatch {
+        /// The type holding the field.
+        type_name: String,
+
+        /// The field name.
+        field: String,
+
+        /// The size of the field on the target.
+        expected: u32,
+
+        /// The size of the Rust field.
+        actual: usize,
+    },
+
+    /// Raised when a register name or index is unknown to the target.
+    #[error("Register not found: {0}")]
+    RegisterNotFound(String),
+
+    /// Raised when an expression cannot be evaluated by the target.
+    #[error("Invalid expression: {0}")]
+    InvalidExpression(String),
+
+    /// Raised when no breakpoint has the given identifier.
+    #[error("Breakpoint not found: {0}")]
+    BreakpointNotFound(u32),
+
+    /// Raised when a byte signature or mask cannot be parsed.
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+
+    /// Raised when the memory at the given address does not hold a valid PE image.
+    #[error("Invalid image at {0:#x}")]
+    InvalidImage(u64),
+
+    /// Raised when a saved snapshot is malformed.
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+
+    /// Raised when a minidump file is malformed or uses unsupported features.
+    #[error("Invalid minidump: {0}")]
+    InvalidMinidump(&'static str),
+
+    /// Raised when a GDB remote stub rejects a request or violates the protocol.
+    #[error("GDB remote error: {0}")]
+    GdbError(String),
+
+    /// Raised when a replayed session has no answer for a call or the recording is malformed.
+    #[error("Replay error: {0}")]
+    Replay(String),
+
+    /// An error returned by the original target, served back from a recording.
+    #[error("Recorded error: {0}")]
+    Recorded(String),
+
     /// Raised when a Windows API call fails.
+    #[cfg(windows)]
     #[error("Windows API error: {0}")]
     WindowsError(#[from] windows::core::Error),
 
@@ -0,0 +1,428 @@
+//! Reacting to debugger events from Rust.
+//!
+//! Implement [`EventHandler`] and register it with `Dbg::set_event_handler` to be notified
+//! of breakpoints, exceptions, process and thread lifetime, and module loads.
+
+#[cfg(windows)]
+use std::cell::RefCell;
+#[cfg(windows)]
+use windows::{
+    Win32::System::Diagnostics::Debug::{EXCEPTION_RECORD64, Extensions::*},
+    core::{Error, HRESULT, PCSTR, Ref, implement},
+};
+
+/// The execution status of the target, matching the `DEBUG_STATUS_*` constants.
+///
+/// Returned by [`EventHandler`] methods to tell the engine how to proceed after an event,
+/// and by `Dbg::execution_status`.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ExecutionStatus {
+    /// Leave the execution status unchanged.
+    #[default]
+    NoChange = 0,
+
+    /// Resume execution.
+    Go = 1,
+
+    /// Resume execution, marking the exception as handled.
+    GoHandled = 2,
+
+    /// Resume execution, marking the exception as not handled.
+    GoNotHandled = 3,
+
+    /// Step over the next instruction.
+    StepOver = 4,
+
+    /// Step into the next instruction.
+    StepInto = 5,
+
+    /// Break into the debugger.
+    Break = 6,
+
+    /// There is no target being debugged.
+    NoDebuggee = 7,
+
+    /// Run until the next branch instruction.
+    StepBranch = 8,
+
+    /// Ignore the event and resume with the previous status.
+    IgnoreEvent = 9,
+
+    /// Request a restart of the target.
+    RestartRequested = 10,
+}
+
+impl TryFrom<u32> for ExecutionStatus {
+    type Error = u32;
+
+    /// Converts a raw `DEBUG_STATUS_*` constant into an [`ExecutionStatus`].
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => ExecutionStatus::NoChange,
+            1 => ExecutionStatus::Go,
+            2 => ExecutionStatus::GoHandled,
+            3 => ExecutionStatus::GoNotHandled,
+            4 => ExecutionStatus::StepOver,
+            5 => ExecutionStatus::StepInto,
+            6 => ExecutionStatus::Break,
+            7 => ExecutionStatus::NoDebuggee,
+            8 => ExecutionStatus::StepBranch,
+            9 => ExecutionStatus::IgnoreEvent,
+            10 => ExecutionStatus::RestartRequested,
+            other => return Err(other),
+        })
+    }
+}
+
+/// A breakpoint was hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakpointEvent {
+    /// The engine identifier of the breakpoint.
+    pub id: u32,
+
+    /// The address of the breakpoint, if the engine could resolve it.
+    pub offset: Option<u64>,
+}
+
+/// An exception was raised in the target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExceptionEvent {
+    /// The exception code, e.g. `0xC0000005` for an access violation.
+    pub code: u32,
+
+    /// The exception flags.
+    pub flags: u32,
+
+    /// The address where the exception occurred.
+    pub address: u64,
+
+    /// The exception parameters.
+    pub parameters: Vec<u64>,
+
+    /// Whether this is the first chance to handle the exception.
+    pub first_chance: bool,
+}
+
+/// A process was created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateProcessEvent {
+    /// The handle of the process.
+    pub handle: u64,
+
+    /// The base address of the process image.
+    pub base: u64,
+
+    /// The size of the process image in bytes.
+    pub size: u32,
+
+    /// The module name of the process image.
+    pub module_name: String,
+
+    /// The file name of the process image.
+    pub image_name: String,
+
+    /// The checksum from the image header.
+    pub checksum: u32,
+
+    /// The timestamp from the image header.
+    pub timestamp: u32,
+
+    /// The start address of the initial thread.
+    pub start_offset: u64,
+}
+
+/// A process exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitProcessEvent {
+    /// The exit code of the process.
+    pub exit_code: u32,
+}
+
+/// A thread was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreateThreadEvent {
+    /// The handle of the thread.
+    pub handle: u64,
+
+    /// The address of the thread data, such as the TEB.
+    pub data_offset: u64,
+
+    /// The start address of the thread.
+    pub start_offset: u64,
+}
+
+/// A thread exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitThreadEvent {
+    /// The exit code of the thread.
+    pub exit_code: u32,
+}
+
+/// A module was loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadModuleEvent {
+    /// The base address of the module.
+    pub base: u64,
+
+    /// The size of the module in bytes.
+    pub size: u32,
+
+    /// The module name.
+    pub module_name: String,
+
+    /// The file name of the module image.
+    pub image_name: String,
+
+    /// The checksum from the image header.
+    pub checksum: u32,
+
+    /// The timestamp from the image header.
+    pub timestamp: u32,
+}
+
+/// A module was unloaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnloadModuleEvent {
+    /// The base name of the module image, if known.
+    pub image_base_name: String,
+
+    /// The base address of the module.
+    pub base: u64,
+}
+
+/// Callbacks invoked when the target reports an event.
+///
+/// Every method defaults to [`ExecutionStatus::NoChange`], so handlers only implement
+/// the events they care about.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// struct Tracer;
+///
+/// impl EventHandler for Tracer {
+///     fn load_module(&mut self, event: &LoadModuleEvent) -> ExecutionStatus {
+///         println!("{} loaded at {:#x}", event.module_name, event.base);
+///         ExecutionStatus::Go
+///     }
+/// }
+///
+/// dbg.set_event_handler(Tracer)?;
+/// ```
+pub trait EventHandler {
+    /// Called when a breakpoint is hit.
+    fn breakpoint(&mut self, _event: &BreakpointEvent) -> ExecutionStatus {
+        ExecutionStatus::NoChange
+    }
+
+    /// Called when an exception is raised.
+    fn exception(&mut self, _event: &ExceptionEvent) -> ExecutionStatus {
+        ExecutionStatus::NoChange
+    }
+
+    /// Called when a process is created.
+    fn create_process(&mut self, _event: &CreateProcessEvent) -> ExecutionStatus {
+        ExecutionStatus::NoChange
+    }
+
+    /// Called when a process exits.
+    fn exit_process(&mut self, _event: &ExitProcessEvent) -> ExecutionStatus {
+        ExecutionStatus::NoChange
+    }
+
+    /// Called when a thread is created.
+    fn create_thread(&mut self, _event: &CreateThreadEvent) -> ExecutionStatus {
+        ExecutionStatus::NoChange
+    }
+
+    /// Called when a thread exits.
+    fn exit_thread(&mut self, _event: &ExitThreadEvent) -> ExecutionStatus {
+        ExecutionStatus::NoChange
+    }
+
+    /// Called when a module is loaded.
+    fn load_module(&mut self, _event: &LoadModuleEvent) -> ExecutionStatus {
+        ExecutionStatus::NoChange
+    }
+
+    /// Called when a module is unloaded.
+    fn unload_module(&mut self, _event: &UnloadModuleEvent) -> ExecutionStatus {
+        ExecutionStatus::NoChange
+    }
+}
+
+/// An `IDebugEventCallbacks` implementation forwarding events to an [`EventHandler`].
+#[cfg(windows)]
+#[implement(IDebugEventCallbacks)]
+pub(crate) struct EventBridge {
+    /// The handler receiving the events.
+    pub(crate) handler: RefCell<Box<dyn EventHandler>>,
+}
+
+#[cfg(windows)]
+impl EventBridge {
+    /// Runs `f` on the handler and converts the requested status into the callback result.
+    ///
+    /// Events raised while the handler is already running are left unchanged.
+    fn dispatch(&self, f: impl FnOnce(&mut dyn EventHandler) -> ExecutionStatus) -> windows::core::Result<()> {
+        let status = match self.handler.try_borrow_mut() {
+            Ok(mut handler) => f(handler.as_mut()),
+            Err(_) => ExecutionStatus::NoChange,
+        };
+
+        // The engine reads the status from the returned HRESULT, where `NoChange` is `S_OK`
+        match status {
+            ExecutionStatus::NoChange => Ok(()),
+            status => Err(Error::from_hresult(HRESULT(status as i32))),
+        }
+    }
+}
+
+/// Converts a possibly null C string passed to a callback into a [`String`].
+#[cfg(windows)]
+fn pcstr_to_string(s: &PCSTR) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+
+    String::from_utf8_lossy(unsafe { s.as_bytes() }).to_string()
+}
+
+#[cfg(windows)]
+impl IDebugEventCallbacks_Impl for EventBridge_Impl {
+    fn GetInterestMask(&self) -> windows::core::Result<u32> {
+        Ok(DEBUG_EVENT_BREAKPOINT
+            | DEBUG_EVENT_EXCEPTION
+            | DEBUG_EVENT_CREATE_THREAD
+            | DEBUG_EVENT_EXIT_THREAD
+            | DEBUG_EVENT_CREATE_PROCESS
+            | DEBUG_EVENT_EXIT_PROCESS
+            | DEBUG_EVENT_LOAD_MODULE
+            | DEBUG_EVENT_UNLOAD_MODULE)
+    }
+
+    fn Breakpoint(&self, bp: Ref<'_, IDebugBreakpoint>) -> windows::core::Result<()> {
+        let bp = bp.ok()?;
+        let event = BreakpointEvent {
+            id: unsafe { bp.GetId()? },
+            offset: unsafe { bp.GetOffset().ok() },
+        };
+
+        self.dispatch(|handler| handler.breakpoint(&event))
+    }
+
+    fn Exception(&self, exception: *const EXCEPTION_RECORD64, firstchance: u32) -> windows::core::Result<()> {
+        let Some(record) = (unsafe { exception.as_ref() }) else {
+            return Ok(());
+        };
+
+        let count = (record.NumberParameters as usize).min(record.ExceptionInformation.len());
+        let event = ExceptionEvent {
+            code: record.ExceptionCode.0 as u32,
+            flags: record.ExceptionFlags,
+            address: record.ExceptionAddress,
+            parameters: record.ExceptionInformation[..count].to_vec(),
+            first_chance: firstchance != 0,
+        };
+
+        self.dispatch(|handler| handler.exception(&event))
+    }
+
+    fn CreateThread(&self, handle: u64, dataoffset: u64, startoffset: u64) -> windows::core::Result<()> {
+        let event = CreateThreadEvent {
+            handle,
+            data_offset: dataoffset,
+            start_offset: startoffset,
+        };
+
+        self.dispatch(|handler| handler.create_thread(&event))
+    }
+
+    fn ExitThread(&self, exitcode: u32) -> windows::core::Result<()> {
+        self.dispatch(|handler| handler.exit_thread(&ExitThreadEvent { exit_code: exitcode }))
+    }
+
+    fn CreateProcessA(
+        &self,
+        _imagefilehandle: u64,
+        handle: u64,
+        baseoffset: u64,
+        modulesize: u32,
+        modulename: &PCSTR,
+        imagename: &PCSTR,
+        checksum: u32,
+        timedatestamp: u32,
+        _initialthreadhandle: u64,
+        _threaddataoffset: u64,
+        startoffset: u64,
+    ) -> windows::core::Result<()> {
+        let event = CreateProcessEvent {
+            handle,
+            base: baseoffset,
+            size: modulesize,
+            module_name: pcstr_to_string(modulename),
+            image_name: pcstr_to_string(imagename),
+            checksum,
+            timestamp: timedatestamp,
+            start_offset: startoffset,
+        };
+
+        self.dispatch(|handler| handler.create_process(&event))
+    }
+
+    fn ExitProcess(&self, exitcode: u32) -> windows::core::Result<()> {
+        self.dispatch(|handler| handler.exit_process(&ExitProcessEvent { exit_code: exitcode }))
+    }
+
+    fn LoadModule(
+        &self,
+        _imagefilehandle: u64,
+        baseoffset: u64,
+        modulesize: u32,
+        modulename: &PCSTR,
+        imagename: &PCSTR,
+        checksum: u32,
+        timedatestamp: u32,
+    ) -> windows::core::Result<()> {
+        let event = LoadModuleEvent {
+            base: baseoffset,
+            size: modulesize,
+            module_name: pcstr_to_string(modulename),
+            image_name: pcstr_to_string(imagename),
+            checksum,
+            timestamp: timedatestamp,
+        };
+
+        self.dispatch(|handler| handler.load_module(&event))
+    }
+
+    fn UnloadModule(&self, imagebasename: &PCSTR, baseoffset: u64) -> windows::core::Result<()> {
+        let event = UnloadModuleEvent {
+            image_base_name: pcstr_to_string(imagebasename),
+            base: baseoffset,
+        };
+
+        self.dispatch(|handler| handler.unload_module(&event))
+    }
+
+    fn SystemError(&self, _error: u32, _level: u32) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn SessionStatus(&self, _status: u32) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn ChangeDebuggeeState(&self, _flags: u32, _argument: u64) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn ChangeEngineState(&self, _flags: u32, _argument: u64) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn ChangeSymbolState(&self, _flags: u32, _argument: u64) -> windows::core::Result<()> {
+        Ok(())
+    }
+}
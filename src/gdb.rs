@@ -0,0 +1,757 @@
+//! A client for the GDB Remote Serial Protocol.
+//!
+//! [`GdbTarget`] drives a `gdbserver` or QEMU's gdbstub over TCP through the same memory
+//! and register API as [`Dbg`](crate::Dbg), which covers targets that the Windows debugging
+//! engine cannot attach to, such as early-boot or hypervisor code.
+
+use std::{
+    cell::{Cell, RefCell},
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+};
+use crate::{
+    error::DbgError,
+    pod::Pod,
+    target::{Memory, Registers},
+    utils::encode_hex,
+    value::Value,
+};
+
+/// Maximum number of bytes requested by a single `m` or `M` packet.
+const MAX_CHUNK: usize = 0x800;
+
+/// Register names of an x86-64 target, in the order used by GDB's register numbers.
+const AMD64_REGISTERS: &[&str] = &[
+    "rax", "rbx", "rcx", "rdx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11", "r12", "r13", "r14", "r15", "rip", "efl", "cs", "ss", "ds",
+    "es", "fs", "gs",
+];
+
+/// Number of 64-bit registers at the start of [`AMD64_REGISTERS`], the rest being 32-bit.
+const AMD64_WIDE_REGISTERS: usize = 17;
+
+/// Register names that differ between the debugging engine and GDB target descriptions, so
+/// code written against [`Dbg`](crate::Dbg) finds the same registers on either.
+const REGISTER_ALIASES: &[(&str, &str)] = &[("efl", "eflags")];
+
+/// The kind of breakpoint inserted with the `Z` packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GdbBreakpointKind {
+    /// A software breakpoint (`Z0`).
+    Software,
+
+    /// A hardware execution breakpoint (`Z1`).
+    Hardware,
+
+    /// A hardware watchpoint triggered on writes (`Z2`).
+    Write,
+
+    /// A hardware watchpoint triggered on reads (`Z3`).
+    Read,
+
+    /// A hardware watchpoint triggered on any access (`Z4`).
+    Access,
+}
+
+/// The reason reported by the stub when the target stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GdbStop {
+    /// The target stopped with the given signal, e.g. `5` (`SIGTRAP`) after a step or breakpoint.
+    Signal(u8),
+
+    /// The process exited with the given status.
+    Exited(u8),
+
+    /// The process was terminated by the given signal.
+    Terminated(u8),
+}
+
+/// A target controlled through the GDB Remote Serial Protocol.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let gdb = GdbTarget::connect("127.0.0.1:1234")?;
+/// gdb.insert_breakpoint(GdbBreakpointKind::Hardware, 0xFFFF_F800_0000_1000, 1)?;
+/// gdb.cont()?;
+///
+/// let rip = gdb.reg_values(&gdb.reg_indices(&["rip"])?)?;
+/// ```
+#[derive(Debug)]
+pub struct GdbTarget {
+    /// The connection to the remote stub.
+    stream: RefCell<BufReader<TcpStream>>,
+
+    /// Register names, indexed by their GDB register number.
+    registers: Vec<String>,
+
+    /// Register sizes in bytes, used to split `g` replies, empty if the layout is unknown.
+    sizes: Vec<usize>,
+
+    /// Bumped whenever the target resumes or memory is written, see [`Memory::generation`].
+    generation: Cell<u64>,
+}
+
+impl GdbTarget {
+    /// Connects to a remote stub, assuming an x86-64 register layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the stub, e.g. `127.0.0.1:1234`.
+    pub fn connect<A>(addr: A) -> Result<Self, DbgError>
+    where
+        A: ToSocketAddrs,
+    {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream: RefCell::new(BufReader::new(stream)),
+            registers: AMD64_REGISTERS.iter().map(|r| r.to_string()).collect(),
+            sizes: (0..AMD64_REGISTERS.len())
+                .map(|i| if i < AMD64_WIDE_REGISTERS { 8 } else { 4 })
+                .collect(),
+            generation: Cell::new(0),
+        })
+    }
+
+    /// Replaces the register layout used to map names to GDB register numbers.
+    ///
+    /// Register sizes are unknown for custom layouts, so the stub must support `p` packets.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The register names, in the order of the target description.
+    pub fn with_registers(mut self, names: &[&str]) -> Self {
+        self.registers = names.iter().map(|r| r.to_string()).collect();
+        self.sizes.clear();
+        self
+    }
+
+    /// Reads a range of virtual memory into a buffer.
+    ///
+    /// Reading stops at the first chunk the stub refuses to read.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual address to read from.
+    /// * `buffer` - A mutable slice where the read bytes will be stored.
+    pub fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        let mut read = 0;
+        for chunk in buffer.chunks_mut(MAX_CHUNK) {
+            let reply = self.request(&format!("m{:x},{:x}", vaddr.wrapping_add(read as u64), chunk.len()))?;
+            if self.check(&reply).is_err() {
+                break;
+            }
+
+            let bytes = decode_reply(&reply)?;
+            let len = bytes.len().min(chunk.len());
+            chunk[..len].copy_from_slice(&bytes[..len]);
+            read += len;
+
+            if len < chunk.len() {
+                break;
+            }
+        }
+
+        if read == 0 && !buffer.is_empty() {
+            return Err(DbgError::InvalidAddress(vaddr));
+        }
+
+        Ok(read)
+    }
+
+    /// Writes a buffer to virtual memory.
+    ///
+    /// Fails with [`DbgError::PartialWrite`] if the stub refuses part of the buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual address to write to.
+    /// * `buffer` - The bytes to write.
+    pub fn write_vaddr(&self, vaddr: u64, buffer: &[u8]) -> Result<(), DbgError> {
+        self.bump_generation();
+        let mut written = 0;
+        for chunk in buffer.chunks(MAX_CHUNK) {
+            let addr = vaddr.wrapping_add(written as u64);
+            let reply = self.request(&format!("M{addr:x},{:x}:{}", chunk.len(), encode_hex(chunk)))?;
+            if reply != "OK" {
+                break;
+            }
+
+            written += chunk.len();
+        }
+
+        match written {
+            0 if !buffer.is_empty() => Err(DbgError::InvalidAddress(vaddr)),
+            written if written < buffer.len() => Err(DbgError::PartialWrite {
+                requested: buffer.len(),
+                written,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads a value of a plain-old-data type from a given virtual memory address.
+    ///
+    /// Fails with [`DbgError::PartialRead`] if only part of the value could be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual memory address to read from.
+    pub fn read_type_vaddr<T: Pod>(&self, vaddr: u64) -> Result<T, DbgError> {
+        Memory::read_type_vaddr(self, vaddr)
+    }
+
+    /// Retrieves the register indices corresponding to a provided list of names.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The names of the registers, e.g. `rip`. Both the engine's names, such as
+    ///   `efl`, and GDB's, such as `eflags`, are accepted.
+    pub fn reg_indices(&self, names: &[&str]) -> Result<Vec<u32>, DbgError> {
+        names
+            .iter()
+            .map(|&n| {
+                self.registers
+                    .iter()
+                    .position(|name| same_register(name, n))
+                    .map(|index| index as u32)
+                    .ok_or_else(|| DbgError::RegisterNotFound(n.to_string()))
+            })
+            .collect()
+    }
+
+    /// Retrieves the values of the registers corresponding to a provided list of indices.
+    ///
+    /// Registers are read one by one with `p` packets, falling back to a single `g` packet
+    /// for stubs that do not support them.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - The GDB register numbers to read.
+    pub fn reg_values(&self, indices: &[u32]) -> Result<Vec<Value>, DbgError> {
+        let mut all = None;
+        indices
+            .iter()
+            .map(|&i| {
+                let reply = self.request(&format!("p{i:x}"))?;
+                let bytes = match reply.as_str() {
+                    "" => self.register_from_all(&mut all, i)?,
+                    _ => self.check(&reply).and_then(|_| decode_reply(&reply))?,
+                };
+
+                // Registers are transferred in target byte order, which is little-endian on x86
+                let mut raw = [0u8; 8];
+                let len = bytes.len().min(raw.len());
+                raw[..len].copy_from_slice(&bytes[..len]);
+
+                Ok(Value::I64(u64::from_le_bytes(raw)))
+            })
+            .collect()
+    }
+
+    /// Writes a register value.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The GDB register number.
+    /// * `value` - The new value, stored in the low bytes of the register.
+    /// * `size` - The size of the register in bytes.
+    pub fn set_reg_value(&self, index: u32, value: u64, size: usize) -> Result<(), DbgError> {
+        let bytes = value.to_le_bytes();
+        let reply = self.request(&format!("P{index:x}={}", encode_hex(&bytes[..size.min(bytes.len())])))?;
+        self.check(&reply)
+    }
+
+    /// Executes a single instruction and waits for the target to stop.
+    pub fn step(&self) -> Result<GdbStop, DbgError> {
+        self.bump_generation();
+        parse_stop(&self.resume("s")?)
+    }
+
+    /// Resumes the target and waits until it stops.
+    pub fn cont(&self) -> Result<GdbStop, DbgError> {
+        self.bump_generation();
+        parse_stop(&self.resume("c")?)
+    }
+
+    /// Returns a handle that can interrupt the target from another thread.
+    ///
+    /// This is the only way to stop a target while [`GdbTarget::cont`] is blocked
+    /// waiting for a stop reply.
+    pub fn interrupt_handle(&self) -> Result<GdbInterrupt, DbgError> {
+        Ok(GdbInterrupt {
+            stream: self.stream.borrow().get_ref().try_clone()?,
+        })
+    }
+
+    /// Queries why the target is currently stopped.
+    pub fn stop_reason(&self) -> Result<GdbStop, DbgError> {
+        parse_stop(&self.request("?")?)
+    }
+
+    /// Inserts a breakpoint or watchpoint.
+    ///
+    /// Unlike [`Dbg::add_breakpoint`](crate::Dbg::add_breakpoint), the stub keeps no handle for
+    /// it, so it is deleted by passing the same arguments to [`GdbTarget::delete_breakpoint`].
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of breakpoint.
+    /// * `addr` - The address to break on.
+    /// * `size` - The breakpoint kind for code breakpoints, or the watched length in bytes.
+    pub fn insert_breakpoint(&self, kind: GdbBreakpointKind, addr: u64, size: usize) -> Result<(), DbgError> {
+        let reply = self.request(&format!("Z{},{addr:x},{size:x}", kind.code()))?;
+        self.check(&reply)
+    }
+
+    /// Deletes a breakpoint or watchpoint inserted with [`GdbTarget::insert_breakpoint`].
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of breakpoint.
+    /// * `addr` - The address of the breakpoint.
+    /// * `size` - The same size passed when inserting it.
+    pub fn delete_breakpoint(&self, kind: GdbBreakpointKind, addr: u64, size: usize) -> Result<(), DbgError> {
+        let reply = self.request(&format!("z{},{addr:x},{size:x}", kind.code()))?;
+        self.check(&reply)
+    }
+
+    /// Sends a monitor command (`qRcmd`) to the stub and returns its console output.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command, e.g. `info registers` for QEMU.
+    pub fn monitor(&self, command: &str) -> Result<String, DbgError> {
+        // Monitor commands may change memory behind our back
+        self.bump_generation();
+        let mut stream = self.stream.borrow_mut();
+        send_packet(&mut stream, &format!("qRcmd,{}", encode_hex(command.as_bytes())))?;
+
+        // The stub streams `O` packets with console output until the final reply
+        let mut output = Vec::new();
+        loop {
+            let reply = recv_packet(&mut stream)?;
+            match reply.strip_prefix('O') {
+                Some(hex) if reply != "OK" => output.extend(decode_reply(hex)?),
+                _ => {
+                    self.check(&reply)?;
+                    return Ok(String::from_utf8_lossy(&output).to_string());
+                }
+            }
+        }
+    }
+
+    /// Extracts a register from a `g` reply, sending the packet the first time.
+    ///
+    /// # Arguments
+    ///
+    /// * `all` - The decoded `g` reply, filled on first use.
+    /// * `index` - The GDB register number.
+    fn register_from_all(&self, all: &mut Option<Vec<u8>>, index: u32) -> Result<Vec<u8>, DbgError> {
+        let index = index as usize;
+        let Some(&size) = self.sizes.get(index) else {
+            return Err(DbgError::GdbError("packet not supported by the stub".to_string()));
+        };
+
+        if all.is_none() {
+            let reply = self.request("g")?;
+            self.check(&reply)?;
+            *all = Some(decode_reply(&reply)?);
+        }
+
+        let start = self.sizes[..index].iter().sum::<usize>();
+        all.as_deref()
+            .and_then(|all| all.get(start..start + size))
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| DbgError::GdbError(format!("register {index} missing from g reply")))
+    }
+
+    /// Marks every cached view of the target memory as stale.
+    fn bump_generation(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
+
+    /// Sends a packet and waits for the reply.
+    fn request(&self, packet: &str) -> Result<String, DbgError> {
+        let mut stream = self.stream.borrow_mut();
+        send_packet(&mut stream, packet)?;
+        recv_packet(&mut stream)
+    }
+
+    /// Sends a resume packet and waits for the stop reply, skipping the `O` console output
+    /// packets the stub may send while the target runs.
+    fn resume(&self, packet: &str) -> Result<String, DbgError> {
+        let mut stream = self.stream.borrow_mut();
+        send_packet(&mut stream, packet)?;
+        loop {
+            let reply = recv_packet(&mut stream)?;
+            if !reply.starts_with('O') || reply == "OK" {
+                return Ok(reply);
+            }
+        }
+    }
+
+    /// Converts an `Exx` or empty reply into an error.
+    fn check(&self, reply: &str) -> Result<(), DbgError> {
+        match reply {
+            "" => Err(DbgError::GdbError("packet not supported by the stub".to_string())),
+            // Data replies always have an even length, so `Exx` cannot be confused with them
+            r if r.starts_with('E') && r.len() == 3 => Err(DbgError::GdbError(format!("stub replied with error {}", &r[1..]))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A handle sending break requests to a running target, obtained through [`GdbTarget::interrupt_handle`].
+#[derive(Debug)]
+pub struct GdbInterrupt {
+    /// A clone of the connection to the remote stub.
+    stream: TcpStream,
+}
+
+impl GdbInterrupt {
+    /// Asks the stub to stop the target.
+    ///
+    /// The stop is reported as the reply to the pending [`GdbTarget::cont`] call.
+    pub fn interrupt(&self) -> Result<(), DbgError> {
+        // The interrupt byte is sent on its own, outside of any packet
+        Ok((&self.stream).write_all(&[0x03])?)
+    }
+}
+
+impl GdbBreakpointKind {
+    /// Returns the type number used in `Z` and `z` packets.
+    fn code(self) -> u8 {
+        match self {
+            GdbBreakpointKind::Software => 0,
+            GdbBreakpointKind::Hardware => 1,
+            GdbBreakpointKind::Write => 2,
+            GdbBreakpointKind::Read => 3,
+            GdbBreakpointKind::Access => 4,
+        }
+    }
+}
+
+impl Memory for GdbTarget {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        GdbTarget::read_vaddr(self, vaddr, buffer)
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+}
+
+impl Registers for GdbTarget {
+    fn reg_indices(&self, names: &[&str]) -> Result<Vec<u32>, DbgError> {
+        GdbTarget::reg_indices(self, names)
+    }
+
+    fn reg_values(&self, indices: &[u32]) -> Result<Vec<Value>, DbgError> {
+        GdbTarget::reg_values(self, indices)
+    }
+}
+
+/// Returns whether two register names refer to the same register, accounting for
+/// [`REGISTER_ALIASES`].
+fn same_register(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+        || REGISTER_ALIASES.iter().any(|(engine, gdb)| {
+            (a.eq_ignore_ascii_case(engine) && b.eq_ignore_ascii_case(gdb)) || (a.eq_ignore_ascii_case(gdb) && b.eq_ignore_ascii_case(engine))
+        })
+}
+
+/// Frames `payload` as `$payload#checksum` and waits for the stub to acknowledge it.
+fn send_packet(stream: &mut BufReader<TcpStream>, payload: &str) -> Result<(), DbgError> {
+    let checksum = payload.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+    let packet = format!("${payload}#{checksum:02x}");
+
+    loop {
+        stream.get_mut().write_all(packet.as_bytes())?;
+
+        let mut ack = [0u8; 1];
+        stream.read_exact(&mut ack)?;
+        match ack[0] {
+            b'+' => return Ok(()),
+            b'-' => continue,
+            other => return Err(DbgError::GdbError(format!("unexpected acknowledgement {other:#x}"))),
+        }
+    }
+}
+
+/// Reads the next packet, acknowledges it and returns its decoded payload.
+fn recv_packet(stream: &mut BufReader<TcpStream>) -> Result<String, DbgError> {
+    loop {
+        // Skip anything before the start of the packet, such as stray acknowledgements
+        let mut skipped = Vec::new();
+        stream.read_until(b'$', &mut skipped)?;
+        if skipped.last() != Some(&b'$') {
+            return Err(DbgError::GdbError("connection closed".to_string()));
+        }
+
+        let mut body = Vec::new();
+        stream.read_until(b'#', &mut body)?;
+        body.pop();
+
+        let mut checksum = [0u8; 2];
+        stream.read_exact(&mut checksum)?;
+
+        let expected = u8::from_str_radix(std::str::from_utf8(&checksum).unwrap_or_default(), 16).ok();
+        if expected != Some(body.iter().fold(0u8, |sum, &b| sum.wrapping_add(b))) {
+            stream.get_mut().write_all(b"-")?;
+            continue;
+        }
+
+        stream.get_mut().write_all(b"+")?;
+        return Ok(String::from_utf8_lossy(&unescape(&body)).to_string());
+    }
+}
+
+/// Expands `}` escapes and `*` run-length encoding in a packet body.
+fn unescape(body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len());
+    let mut bytes = body.iter().copied();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'}' => out.extend(bytes.next().map(|b| b ^ 0x20)),
+            b'*' => {
+                let (Some(&last), Some(count)) = (out.last(), bytes.next()) else {
+                    continue;
+                };
+
+                out.extend(std::iter::repeat_n(last, count.saturating_sub(29) as usize));
+            }
+            _ => out.push(b),
+        }
+    }
+
+    out
+}
+
+/// Parses a stop reply packet (`S`, `T`, `W` or `X`).
+fn parse_stop(reply: &str) -> Result<GdbStop, DbgError> {
+    let code = reply
+        .get(1..3)
+        .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| DbgError::GdbError(format!("malformed stop reply: {reply}")))?;
+
+    match reply.as_bytes()[0] {
+        b'S' | b'T' => Ok(GdbStop::Signal(code)),
+        b'W' => Ok(GdbStop::Exited(code)),
+        b'X' => Ok(GdbStop::Terminated(code)),
+        _ => Err(DbgError::GdbError(format!("unexpected stop reply: {reply}"))),
+    }
+}
+
+/// Decodes the hexadecimal payload of a reply, treating unavailable bytes (`xx`) as zero.
+fn decode_reply(hex: &str) -> Result<Vec<u8>, DbgError> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            b"xx" => Ok(0),
+            _ => std::str::from_utf8(pair)
+                .ok()
+                .and_then(|s| u8::from_str_radix(s, 16).ok())
+                .ok_or_else(|| DbgError::GdbError(format!("invalid hex in reply: {hex}"))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{net::TcpListener, thread};
+
+    /// Frames a payload with a valid checksum.
+    fn packet(payload: &str) -> String {
+        let checksum = payload.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        format!("${payload}#{checksum:02x}")
+    }
+
+    /// Frames a payload with a wrong checksum, which the client must reject.
+    fn corrupt(payload: &str) -> String {
+        let checksum = payload.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        format!("${payload}#{:02x}", checksum.wrapping_add(1))
+    }
+
+    /// Reads a packet sent by the client and returns its payload, checking the checksum.
+    fn read_packet(stream: &mut BufReader<TcpStream>) -> String {
+        let mut skipped = Vec::new();
+        stream.read_until(b'$', &mut skipped).unwrap();
+
+        let mut body = Vec::new();
+        stream.read_until(b'#', &mut body).unwrap();
+        body.pop();
+
+        let mut checksum = [0u8; 2];
+        stream.read_exact(&mut checksum).unwrap();
+        let checksum = u8::from_str_radix(std::str::from_utf8(&checksum).unwrap(), 16).unwrap();
+        assert_eq!(checksum, body.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)));
+
+        String::from_utf8(body).unwrap()
+    }
+
+    /// Starts a stub expecting each request of `script` in order and answering it with the
+    /// given framed replies.
+    ///
+    /// The first request is refused once, so every test also covers retransmission.
+    fn stub(script: Vec<(String, Vec<String>)>) -> (GdbTarget, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut stream = BufReader::new(stream);
+
+            let mut refused = false;
+            for (expected, replies) in script {
+                let mut request = read_packet(&mut stream);
+                if !refused {
+                    refused = true;
+                    stream.get_mut().write_all(b"-").unwrap();
+                    request = read_packet(&mut stream);
+                }
+
+                assert_eq!(request, expected);
+                stream.get_mut().write_all(b"+").unwrap();
+
+                for reply in replies {
+                    stream.get_mut().write_all(reply.as_bytes()).unwrap();
+
+                    // The client acknowledges good packets and asks for bad ones again
+                    let mut ack = [0u8; 1];
+                    stream.read_exact(&mut ack).unwrap();
+                    let valid = reply == packet(&reply[1..reply.len() - 3]);
+                    assert_eq!(ack[0], if valid { b'+' } else { b'-' });
+                }
+            }
+        });
+
+        (GdbTarget::connect(addr).unwrap(), handle)
+    }
+
+    /// Builds a script entry answered by a single valid packet.
+    fn exchange(request: &str, reply: &str) -> (String, Vec<String>) {
+        (request.to_string(), vec![packet(reply)])
+    }
+
+    #[test]
+    fn reads_and_writes_memory() {
+        let large = vec![0xCCu8; MAX_CHUNK + 1];
+        let (gdb, stub) = stub(vec![
+            exchange("m1000,4", "11223344"),
+            exchange("m2000,4", "E14"),
+            exchange("m3000,4", "aabb"),
+            exchange("M1000,2:9090", "OK"),
+            exchange("M2000,1:90", "E0e"),
+            exchange(&format!("M4000,{MAX_CHUNK:x}:{}", encode_hex(&large[..MAX_CHUNK])), "OK"),
+            exchange(&format!("M{:x},1:cc", 0x4000 + MAX_CHUNK), "E0e"),
+        ]);
+
+        assert_eq!(gdb.read_type_vaddr::<u32>(0x1000).unwrap(), 0x4433_2211);
+        assert!(matches!(gdb.read_vaddr(0x2000, &mut [0; 4]), Err(DbgError::InvalidAddress(0x2000))));
+        assert_eq!(gdb.read_vaddr(0x3000, &mut [0; 4]).unwrap(), 2);
+
+        let generation = Memory::generation(&gdb);
+        gdb.write_vaddr(0x1000, &[0x90, 0x90]).unwrap();
+        assert_ne!(Memory::generation(&gdb), generation);
+        assert!(matches!(gdb.write_vaddr(0x2000, &[0x90]), Err(DbgError::InvalidAddress(0x2000))));
+        assert!(matches!(
+            gdb.write_vaddr(0x4000, &large),
+            Err(DbgError::PartialWrite { requested, written: MAX_CHUNK }) if requested == MAX_CHUNK + 1
+        ));
+
+        drop(gdb);
+        stub.join().unwrap();
+    }
+
+    #[test]
+    fn reads_registers() {
+        let mut all = String::new();
+        for i in 0..AMD64_REGISTERS.len() {
+            let size = if i < AMD64_WIDE_REGISTERS { 8 } else { 4 };
+            all.push_str(&encode_hex(&(i as u64 + 1).to_le_bytes()[..size]));
+        }
+
+        let (gdb, stub) = stub(vec![
+            exchange("p10", "0010004001000000"),
+            exchange("p7", ""),
+            exchange("g", &all),
+            exchange("p11", ""),
+            exchange("p0", "E01"),
+        ]);
+
+        let indices = gdb.reg_indices(&["RIP", "rsp", "efl"]).unwrap();
+        assert_eq!(indices, vec![16, 7, 17]);
+        assert_eq!(gdb.reg_indices(&["eflags"]).unwrap(), vec![17]);
+        assert_eq!(gdb.reg_values(&indices[..1]).unwrap(), vec![Value::I64(0x0001_4000_1000)]);
+
+        // Without `p` support, registers come from a single `g` packet
+        assert_eq!(gdb.reg_values(&indices[1..]).unwrap(), vec![Value::I64(8), Value::I64(18)]);
+        assert!(matches!(gdb.reg_values(&[0]), Err(DbgError::GdbError(_))));
+        assert!(matches!(gdb.reg_indices(&["xmm0"]), Err(DbgError::RegisterNotFound(_))));
+
+        drop(gdb);
+        stub.join().unwrap();
+    }
+
+    #[test]
+    fn skips_console_output_while_running() {
+        let (gdb, stub) = stub(vec![
+            ("c".to_string(), vec![packet("O48690a"), packet("O"), packet("T05thread:01;")]),
+            ("s".to_string(), vec![packet("O4f4b"), packet("S05")]),
+            ("c".to_string(), vec![packet("W00")]),
+            ("qRcmd,6869".to_string(), vec![packet("O6f6b0a"), packet("OK")]),
+        ]);
+
+        assert_eq!(gdb.cont().unwrap(), GdbStop::Signal(5));
+        assert_eq!(gdb.step().unwrap(), GdbStop::Signal(5));
+        assert_eq!(gdb.cont().unwrap(), GdbStop::Exited(0));
+        assert_eq!(gdb.monitor("hi").unwrap(), "ok\n");
+
+        drop(gdb);
+        stub.join().unwrap();
+    }
+
+    #[test]
+    fn inserts_and_deletes_breakpoints() {
+        let (gdb, stub) = stub(vec![
+            exchange("Z1,fffff80000001000,1", "OK"),
+            exchange("z1,fffff80000001000,1", "OK"),
+            exchange("Z2,4000,8", "E22"),
+        ]);
+
+        gdb.insert_breakpoint(GdbBreakpointKind::Hardware, 0xFFFF_F800_0000_1000, 1)
+            .unwrap();
+        gdb.delete_breakpoint(GdbBreakpointKind::Hardware, 0xFFFF_F800_0000_1000, 1)
+            .unwrap();
+        assert!(matches!(gdb.insert_breakpoint(GdbBreakpointKind::Write, 0x4000, 8), Err(DbgError::GdbError(_))));
+
+        drop(gdb);
+        stub.join().unwrap();
+    }
+
+    #[test]
+    fn rejects_bad_checksums() {
+        let (gdb, stub) = stub(vec![
+            ("m1000,2".to_string(), vec![corrupt("ffff"), packet("3412")]),
+            ("?".to_string(), vec![corrupt("S0b"), corrupt("S0b"), packet("S0b")]),
+        ]);
+
+        assert_eq!(gdb.read_type_vaddr::<u16>(0x1000).unwrap(), 0x1234);
+        assert_eq!(gdb.stop_reason().unwrap(), GdbStop::Signal(11));
+
+        drop(gdb);
+        stub.join().unwrap();
+    }
+
+    #[test]
+    fn decodes_packet_bodies() {
+        assert_eq!(unescape(b"a}]b"), b"a}b");
+        assert_eq!(unescape(b"0* "), b"0000");
+        assert_eq!(decode_reply("01xxff").unwrap(), vec![1, 0, 0xFF]);
+        assert!(decode_reply("zz").is_err());
+        assert!(parse_stop("Q05").is_err());
+        assert_eq!(parse_stop("X09").unwrap(), GdbStop::Terminated(9));
+    }
+}
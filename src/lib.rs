@@ -2,5 +2,85 @@
 
 pub mod error;
 
+mod macros;
+
+#[cfg(windows)]
 mod dbg;
+#[cfg(windows)]
 pub use dbg::*;
+
+mod target;
+pub use target::*;
+
+mod pod;
+pub use pod::*;
+
+mod ptr;
+pub use ptr::*;
+
+mod string;
+pub use string::*;
+
+mod value;
+pub use value::*;
+
+mod module;
+pub use module::*;
+
+mod symbol;
+pub use symbol::*;
+
+mod types;
+pub use types::*;
+
+mod target_struct;
+pub use target_struct::*;
+
+#[cfg(feature = "derive")]
+pub use dbg_rs_derive::TargetStruct;
+
+mod output;
+pub use output::*;
+
+mod event;
+pub use event::*;
+
+mod breakpoint;
+pub use breakpoint::*;
+
+mod space;
+pub use space::*;
+
+mod region;
+pub use region::*;
+
+mod cache;
+pub use cache::*;
+
+mod chain;
+pub use chain::*;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod pattern;
+pub use pattern::*;
+
+mod paging;
+pub use paging::*;
+
+mod minidump;
+pub use minidump::*;
+
+mod gdb;
+pub use gdb::*;
+
+mod record;
+pub use record::*;
+
+mod utils;
+
+#[cfg(feature = "mock")]
+mod mock;
+#[cfg(feature = "mock")]
+pub use mock::*;
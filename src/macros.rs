@@ -0,0 +1,40 @@
+//! Macros for writing formatted output to the debugger.
+
+/// Macro to send formatted messages to the debugger using `Dbg::println`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// dprintln!(dbg, "Hello, {}!", "Debugger");
+/// dprintln!(dbg, "This is a number: {}", 42);
+/// ```
+#[macro_export]
+macro_rules! dprintln {
+    ($dbg:expr) => {
+        $dbg.println("");
+    };
+
+    ($dbg:expr, $($arg:tt)*) => {
+        $dbg.println(format!($($arg)*));
+    };
+}
+
+/// Macro to send formatted messages to the debugger using `Dbg::print`.
+///  
+/// # Examples
+///
+/// ```rust,ignore
+/// dprint!(dbg, "Hello, {}!", "Debugger");
+/// dprint!(dbg, "This is a number: {}", 42);
+/// dprint!(dbg); // Prints an empty message
+/// ```
+#[macro_export]
+macro_rules! dprint {
+    ($dbg:expr) => {
+        $dbg.print("");
+    };
+
+    ($dbg:expr, $($arg:tt)*) => {
+        $dbg.print(format!($($arg)*));
+    };
+}
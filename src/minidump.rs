@@ -0,0 +1,905 @@
+//! A pure Rust reader for Windows minidump (`.dmp`) files.
+//!
+//! [`Minidump`] exposes the memory, modules and threads captured in a dump through the
+//! same API as [`Dbg`](crate::Dbg), so analysis code can run offline and on any OS.
+
+use std::{fs, path::Path};
+use crate::{
+    error::DbgError,
+    module::{ModuleInfo, SymbolType},
+    pod::Pod,
+    ptr::PointerWidth,
+    target::{Memory, Registers},
+    value::Value,
+};
+
+/// The `MDMP` signature found at the start of every minidump.
+const MINIDUMP_SIGNATURE: u32 = 0x504D_444D;
+
+/// Stream containing the threads of the process.
+const THREAD_LIST_STREAM: u32 = 3;
+
+/// Stream containing the loaded modules.
+const MODULE_LIST_STREAM: u32 = 4;
+
+/// Stream containing memory ranges with individual file locations.
+const MEMORY_LIST_STREAM: u32 = 5;
+
+/// Stream describing the exception that triggered the dump.
+const EXCEPTION_STREAM: u32 = 6;
+
+/// Stream describing the operating system and processor.
+const SYSTEM_INFO_STREAM: u32 = 7;
+
+/// Stream containing memory ranges stored back to back, used by full dumps.
+const MEMORY64_LIST_STREAM: u32 = 9;
+
+/// Stream containing the modules unloaded before the dump was written.
+const UNLOADED_MODULE_LIST_STREAM: u32 = 14;
+
+/// `DEBUG_MODULE_UNLOADED`, set on the modules of the `UnloadedModuleListStream`.
+const DEBUG_MODULE_UNLOADED: u32 = 0x1;
+
+/// `PROCESSOR_ARCHITECTURE_INTEL`.
+const PROCESSOR_ARCHITECTURE_INTEL: u16 = 0;
+
+/// `PROCESSOR_ARCHITECTURE_ARM`.
+const PROCESSOR_ARCHITECTURE_ARM: u16 = 5;
+
+/// `PROCESSOR_ARCHITECTURE_AMD64`.
+const PROCESSOR_ARCHITECTURE_AMD64: u16 = 9;
+
+/// Names and `CONTEXT` offsets of the x64 registers exposed by [`Minidump::reg_values`].
+const AMD64_REGISTERS: &[(&str, usize, usize)] = &[
+    ("rax", 0x78, 8),
+    ("rcx", 0x80, 8),
+    ("rdx", 0x88, 8),
+    ("rbx", 0x90, 8),
+    ("rsp", 0x98, 8),
+    ("rbp", 0xA0, 8),
+    ("rsi", 0xA8, 8),
+    ("rdi", 0xB0, 8),
+    ("r8", 0xB8, 8),
+    ("r9", 0xC0, 8),
+    ("r10", 0xC8, 8),
+    ("r11", 0xD0, 8),
+    ("r12", 0xD8, 8),
+    ("r13", 0xE0, 8),
+    ("r14", 0xE8, 8),
+    ("r15", 0xF0, 8),
+    ("rip", 0xF8, 8),
+    ("efl", 0x44, 4),
+    ("cs", 0x38, 2),
+    ("ds", 0x3A, 2),
+    ("es", 0x3C, 2),
+    ("fs", 0x3E, 2),
+    ("gs", 0x40, 2),
+    ("ss", 0x42, 2),
+    ("dr0", 0x48, 8),
+    ("dr1", 0x50, 8),
+    ("dr2", 0x58, 8),
+    ("dr3", 0x60, 8),
+    ("dr6", 0x68, 8),
+    ("dr7", 0x70, 8),
+];
+
+/// Names and `CONTEXT` offsets of the x86 registers exposed by [`Minidump::reg_values`].
+const X86_REGISTERS: &[(&str, usize, usize)] = &[
+    ("eax", 0xB0, 4),
+    ("ecx", 0xAC, 4),
+    ("edx", 0xA8, 4),
+    ("ebx", 0xA4, 4),
+    ("esp", 0xC4, 4),
+    ("ebp", 0xB4, 4),
+    ("esi", 0xA0, 4),
+    ("edi", 0x9C, 4),
+    ("eip", 0xB8, 4),
+    ("efl", 0xC0, 4),
+    ("cs", 0xBC, 4),
+    ("ds", 0x98, 4),
+    ("es", 0x94, 4),
+    ("fs", 0x90, 4),
+    ("gs", 0x8C, 4),
+    ("ss", 0xC8, 4),
+    ("dr0", 0x04, 4),
+    ("dr1", 0x08, 4),
+    ("dr2", 0x0C, 4),
+    ("dr3", 0x10, 4),
+    ("dr6", 0x14, 4),
+    ("dr7", 0x18, 4),
+];
+
+/// A parsed minidump file.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let dump = Minidump::open("crash.dmp")?;
+/// for module in dump.modules()? {
+///     println!("{:#x} {}", module.base, module.loaded_image_name);
+/// }
+///
+/// let value = dump.read_type_vaddr::<u64>(dump.modules()?[0].base)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Minidump {
+    /// The raw contents of the dump file.
+    data: Vec<u8>,
+
+    /// Captured memory ranges, sorted by virtual address.
+    ranges: Vec<MemoryRange>,
+
+    /// Modules listed in the `ModuleListStream`.
+    modules: Vec<ModuleInfo>,
+
+    /// Modules listed in the `UnloadedModuleListStream`.
+    unloaded_modules: Vec<ModuleInfo>,
+
+    /// Threads listed in the `ThreadListStream`.
+    threads: Vec<MinidumpThread>,
+
+    /// Contents of the `SystemInfoStream`, if present.
+    system_info: Option<MinidumpSystemInfo>,
+
+    /// Contents of the `ExceptionStream`, if present.
+    exception: Option<MinidumpException>,
+
+    /// Index into `threads` of the thread whose registers are reported.
+    current_thread: usize,
+}
+
+/// A range of virtual memory captured in the dump.
+#[derive(Debug, Clone, Copy)]
+struct MemoryRange {
+    /// The virtual address of the first byte.
+    start: u64,
+
+    /// The size of the range in bytes.
+    size: u64,
+
+    /// The offset of the first byte inside the dump file.
+    offset: u64,
+}
+
+/// A thread recorded in the dump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpThread {
+    /// The thread identifier.
+    pub id: u32,
+
+    /// The suspend count of the thread.
+    pub suspend_count: u32,
+
+    /// The priority class of the thread.
+    pub priority_class: u32,
+
+    /// The priority level of the thread.
+    pub priority: u32,
+
+    /// The address of the thread environment block.
+    pub teb: u64,
+
+    /// The start address of the captured stack.
+    pub stack_start: u64,
+
+    /// The size of the captured stack in bytes.
+    pub stack_size: u32,
+
+    /// The raw, architecture specific `CONTEXT` structure of the thread.
+    pub context: Vec<u8>,
+}
+
+/// Operating system and processor information recorded in the dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinidumpSystemInfo {
+    /// The `PROCESSOR_ARCHITECTURE_*` value of the system.
+    pub processor_architecture: u16,
+
+    /// The processor level.
+    pub processor_level: u16,
+
+    /// The processor revision.
+    pub processor_revision: u16,
+
+    /// The number of processors.
+    pub number_of_processors: u8,
+
+    /// The product type (workstation, domain controller or server).
+    pub product_type: u8,
+
+    /// The major version of the operating system.
+    pub major_version: u32,
+
+    /// The minor version of the operating system.
+    pub minor_version: u32,
+
+    /// The build number of the operating system.
+    pub build_number: u32,
+
+    /// The platform identifier of the operating system.
+    pub platform_id: u32,
+}
+
+/// The exception that caused the dump to be written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpException {
+    /// The identifier of the thread that raised the exception.
+    pub thread_id: u32,
+
+    /// The exception code, e.g. `0xC0000005` for an access violation.
+    pub code: u32,
+
+    /// The exception flags.
+    pub flags: u32,
+
+    /// The address where the exception occurred.
+    pub address: u64,
+
+    /// The exception parameters.
+    pub parameters: Vec<u64>,
+
+    /// The raw `CONTEXT` structure captured when the exception was raised.
+    pub context: Vec<u8>,
+}
+
+impl Minidump {
+    /// Reads and parses a minidump file from disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the `.dmp` file.
+    pub fn open<P>(path: P) -> Result<Self, DbgError>
+    where
+        P: AsRef<Path>,
+    {
+        Self::parse(fs::read(path)?)
+    }
+
+    /// Parses a minidump from its raw contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The contents of the `.dmp` file.
+    pub fn parse(data: Vec<u8>) -> Result<Self, DbgError> {
+        if read_u32(&data, 0)? != MINIDUMP_SIGNATURE {
+            return Err(DbgError::InvalidMinidump("bad signature"));
+        }
+
+        let mut dump = Self {
+            data,
+            ranges: Vec::new(),
+            modules: Vec::new(),
+            unloaded_modules: Vec::new(),
+            threads: Vec::new(),
+            system_info: None,
+            exception: None,
+            current_thread: 0,
+        };
+
+        let count = read_u32(&dump.data, 8)? as usize;
+        let directory = read_u32(&dump.data, 12)? as usize;
+        for i in 0..count {
+            let entry = directory + i * 12;
+            let kind = read_u32(&dump.data, entry)?;
+            let rva = read_u32(&dump.data, entry + 8)? as usize;
+
+            match kind {
+                THREAD_LIST_STREAM => dump.threads = dump.parse_threads(rva)?,
+                MODULE_LIST_STREAM => dump.modules = dump.parse_modules(rva)?,
+                UNLOADED_MODULE_LIST_STREAM => dump.unloaded_modules = dump.parse_unloaded_modules(rva)?,
+                MEMORY_LIST_STREAM => dump.ranges.extend(dump.parse_memory(rva)?),
+                MEMORY64_LIST_STREAM => dump.ranges.extend(dump.parse_memory64(rva)?),
+                EXCEPTION_STREAM => dump.exception = Some(dump.parse_exception(rva)?),
+                SYSTEM_INFO_STREAM => dump.system_info = Some(dump.parse_system_info(rva)?),
+                _ => {}
+            }
+        }
+
+        dump.ranges.sort_by_key(|range| range.start);
+
+        // Report the faulting thread by default, like the debugger does when opening a dump
+        if let Some(exception) = &dump.exception {
+            dump.current_thread = dump.threads.iter().position(|t| t.id == exception.thread_id).unwrap_or(0);
+        }
+
+        Ok(dump)
+    }
+
+    /// Returns the modules loaded when the dump was written, like [`Dbg::modules`](crate::Dbg::modules).
+    ///
+    /// Dumps carry no symbol state, so [`ModuleInfo::symbol_type`] is always
+    /// [`SymbolType::None`].
+    pub fn modules(&self) -> Result<Vec<ModuleInfo>, DbgError> {
+        Ok(self.modules.clone())
+    }
+
+    /// Returns the modules unloaded before the dump was written, like
+    /// [`Dbg::unloaded_modules`](crate::Dbg::unloaded_modules).
+    pub fn unloaded_modules(&self) -> Result<Vec<ModuleInfo>, DbgError> {
+        Ok(self.unloaded_modules.clone())
+    }
+
+    /// Returns the threads recorded in the dump.
+    pub fn threads(&self) -> &[MinidumpThread] {
+        &self.threads
+    }
+
+    /// Returns the system information recorded in the dump, if any.
+    pub fn system_info(&self) -> Option<&MinidumpSystemInfo> {
+        self.system_info.as_ref()
+    }
+
+    /// Returns the exception that triggered the dump, if any.
+    pub fn exception(&self) -> Option<&MinidumpException> {
+        self.exception.as_ref()
+    }
+
+    /// Returns the thread whose registers are reported by [`Minidump::reg_values`].
+    pub fn current_thread(&self) -> Option<&MinidumpThread> {
+        self.threads.get(self.current_thread)
+    }
+
+    /// Selects the thread whose registers are reported by [`Minidump::reg_values`].
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The identifier of the thread.
+    pub fn set_current_thread(&mut self, id: u32) -> Result<(), DbgError> {
+        self.current_thread = self
+            .threads
+            .iter()
+            .position(|t| t.id == id)
+            .ok_or(DbgError::InvalidMinidump("unknown thread"))?;
+
+        Ok(())
+    }
+
+    /// Reads a range of virtual memory into a buffer.
+    ///
+    /// Reading stops at the first byte that was not captured in the dump.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual address to read from.
+    /// * `buffer` - A mutable slice where the read bytes will be stored.
+    pub fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        let mut read = 0;
+        while read < buffer.len() {
+            // Stop at the end of the address space instead of wrapping around to zero
+            let Some(addr) = vaddr.checked_add(read as u64) else {
+                break;
+            };
+
+            let Some(range) = self.range(addr) else {
+                break;
+            };
+
+            let skip = addr - range.start;
+            let available = usize::try_from(range.size - skip).unwrap_or(usize::MAX);
+            let len = available.min(buffer.len() - read);
+            let bytes = usize::try_from(range.offset.saturating_add(skip))
+                .ok()
+                .and_then(|offset| self.data.get(offset..offset.checked_add(len)?))
+                .ok_or(DbgError::InvalidMinidump("memory range out of bounds"))?;
+
+            buffer[read..read + len].copy_from_slice(bytes);
+            read += len;
+        }
+
+        if read == 0 && !buffer.is_empty() {
+            return Err(DbgError::InvalidAddress(vaddr));
+        }
+
+        Ok(read)
+    }
+
+    /// Reads a value of a plain-old-data type from a given virtual memory address.
+    ///
+    /// Fails with [`DbgError::PartialRead`] if only part of the value could be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual memory address to read from.
+    pub fn read_type_vaddr<T: Pod>(&self, vaddr: u64) -> Result<T, DbgError> {
+        Memory::read_type_vaddr(self, vaddr)
+    }
+
+    /// Reads a null-terminated C string from a specific virtual memory address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the null-terminated string to read.
+    pub fn read_cstr(&self, addr: u64) -> Result<String, DbgError> {
+        Memory::read_cstr(self, addr)
+    }
+
+    /// Retrieves the register indices corresponding to a provided list of names.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The names of the registers, e.g. `rip` or `eax`.
+    pub fn reg_indices(&self, names: &[&str]) -> Result<Vec<u32>, DbgError> {
+        let table = self.register_table()?;
+        names
+            .iter()
+            .map(|&n| {
+                table
+                    .iter()
+                    .position(|(name, ..)| name.eq_ignore_ascii_case(n))
+                    .map(|index| index as u32)
+                    .ok_or_else(|| DbgError::RegisterNotFound(n.to_string()))
+            })
+            .collect()
+    }
+
+    /// Retrieves the values of the registers of the current thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - The register indices, as returned by [`Minidump::reg_indices`].
+    pub fn reg_values(&self, indices: &[u32]) -> Result<Vec<Value>, DbgError> {
+        let table = self.register_table()?;
+        let thread = self.current_thread().ok_or(DbgError::InvalidMinidump("no threads"))?;
+
+        indices
+            .iter()
+            .map(|&i| {
+                let &(_, offset, size) = table.get(i as usize).ok_or_else(|| DbgError::RegisterNotFound(i.to_string()))?;
+
+                let bytes = thread
+                    .context
+                    .get(offset..offset + size)
+                    .ok_or(DbgError::InvalidMinidump("thread context too small"))?;
+
+                let mut raw = [0u8; 8];
+                raw[..size].copy_from_slice(bytes);
+
+                Ok(Value::I64(u64::from_le_bytes(raw)))
+            })
+            .collect()
+    }
+
+    /// Returns the register layout matching the processor architecture of the dump.
+    fn register_table(&self) -> Result<&'static [(&'static str, usize, usize)], DbgError> {
+        match self.system_info.map(|info| info.processor_architecture) {
+            Some(PROCESSOR_ARCHITECTURE_AMD64) => Ok(AMD64_REGISTERS),
+            Some(PROCESSOR_ARCHITECTURE_INTEL) => Ok(X86_REGISTERS),
+            _ => Err(DbgError::InvalidMinidump("unsupported processor architecture")),
+        }
+    }
+
+    /// Finds the captured range containing `vaddr`.
+    fn range(&self, vaddr: u64) -> Option<&MemoryRange> {
+        let index = self.ranges.partition_point(|range| range.start <= vaddr).checked_sub(1)?;
+        let range = &self.ranges[index];
+        (vaddr - range.start < range.size).then_some(range)
+    }
+
+    /// Reads the bytes described by a `MINIDUMP_LOCATION_DESCRIPTOR`.
+    fn location(&self, offset: usize) -> Result<Vec<u8>, DbgError> {
+        let size = read_u32(&self.data, offset)? as usize;
+        let rva = read_u32(&self.data, offset + 4)? as usize;
+        self.data
+            .get(rva..rva + size)
+            .map(<[u8]>::to_vec)
+            .ok_or(DbgError::InvalidMinidump("location out of bounds"))
+    }
+
+    /// Reads a `MINIDUMP_STRING` stored at `rva`.
+    fn string(&self, rva: usize) -> Result<String, DbgError> {
+        let len = read_u32(&self.data, rva)? as usize;
+        let bytes = self
+            .data
+            .get(rva + 4..rva + 4 + len)
+            .ok_or(DbgError::InvalidMinidump("string out of bounds"))?;
+
+        let units = bytes
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|&c| u16::from_le_bytes(c))
+            .collect::<Vec<_>>();
+        Ok(String::from_utf16_lossy(&units))
+    }
+
+    /// Parses a `MINIDUMP_THREAD_LIST`.
+    fn parse_threads(&self, rva: usize) -> Result<Vec<MinidumpThread>, DbgError> {
+        let count = read_u32(&self.data, rva)? as usize;
+        (0..count)
+            .map(|i| {
+                let entry = rva + 4 + i * 48;
+                Ok(MinidumpThread {
+                    id: read_u32(&self.data, entry)?,
+                    suspend_count: read_u32(&self.data, entry + 4)?,
+                    priority_class: read_u32(&self.data, entry + 8)?,
+                    priority: read_u32(&self.data, entry + 12)?,
+                    teb: read_u64(&self.data, entry + 16)?,
+                    stack_start: read_u64(&self.data, entry + 24)?,
+                    stack_size: read_u32(&self.data, entry + 32)?,
+                    context: self.location(entry + 40)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Parses a `MINIDUMP_MODULE_LIST`.
+    fn parse_modules(&self, rva: usize) -> Result<Vec<ModuleInfo>, DbgError> {
+        let count = read_u32(&self.data, rva)? as usize;
+        (0..count).map(|i| self.module(rva + 4 + i * 108, 0)).collect()
+    }
+
+    /// Parses a `MINIDUMP_UNLOADED_MODULE_LIST`, whose header gives its own size and entry size.
+    fn parse_unloaded_modules(&self, rva: usize) -> Result<Vec<ModuleInfo>, DbgError> {
+        let header = read_u32(&self.data, rva)? as usize;
+        let entry_size = read_u32(&self.data, rva + 4)? as usize;
+        let count = read_u32(&self.data, rva + 8)? as usize;
+        (0..count)
+            .map(|i| self.module(rva + header + i * entry_size, DEBUG_MODULE_UNLOADED))
+            .collect()
+    }
+
+    /// Reads the fields shared by `MINIDUMP_MODULE` and `MINIDUMP_UNLOADED_MODULE` at `entry`.
+    fn module(&self, entry: usize, flags: u32) -> Result<ModuleInfo, DbgError> {
+        let path = self.string(read_u32(&self.data, entry + 20)? as usize)?;
+
+        // Dumps only record the path, derive the other names the way the engine does
+        let image_name = path.rsplit(['\\', '/']).next().unwrap_or_default().to_string();
+        let module_name = image_name
+            .rsplit_once('.')
+            .map_or(image_name.as_str(), |(stem, _)| stem)
+            .to_string();
+
+        Ok(ModuleInfo {
+            base: read_u64(&self.data, entry)?,
+            size: read_u32(&self.data, entry + 8)?,
+            module_name,
+            image_name,
+            loaded_image_name: path,
+            timestamp: read_u32(&self.data, entry + 16)?,
+            checksum: read_u32(&self.data, entry + 12)?,
+            symbol_type: SymbolType::None,
+            flags,
+        })
+    }
+
+    /// Parses a `MINIDUMP_MEMORY_LIST`.
+    fn parse_memory(&self, rva: usize) -> Result<Vec<MemoryRange>, DbgError> {
+        let count = read_u32(&self.data, rva)? as usize;
+        (0..count)
+            .map(|i| {
+                let entry = rva + 4 + i * 16;
+                Ok(MemoryRange {
+                    start: read_u64(&self.data, entry)?,
+                    size: read_u32(&self.data, entry + 8)? as u64,
+                    offset: read_u32(&self.data, entry + 12)? as u64,
+                })
+            })
+            .collect()
+    }
+
+    /// Parses a `MINIDUMP_MEMORY64_LIST`, whose ranges are stored contiguously from a base offset.
+    fn parse_memory64(&self, rva: usize) -> Result<Vec<MemoryRange>, DbgError> {
+        let count = read_u64(&self.data, rva)? as usize;
+        let mut offset = read_u64(&self.data, rva + 8)?;
+        (0..count)
+            .map(|i| {
+                let entry = rva + 16 + i * 16;
+                let range = MemoryRange {
+                    start: read_u64(&self.data, entry)?,
+                    size: read_u64(&self.data, entry + 8)?,
+                    offset,
+                };
+
+                offset = offset
+                    .checked_add(range.size)
+                    .ok_or(DbgError::InvalidMinidump("memory range out of bounds"))?;
+                Ok(range)
+            })
+            .collect()
+    }
+
+    /// Parses a `MINIDUMP_EXCEPTION_STREAM`.
+    fn parse_exception(&self, rva: usize) -> Result<MinidumpException, DbgError> {
+        let record = rva + 8;
+        let count = (read_u32(&self.data, record + 24)? as usize).min(15);
+        Ok(MinidumpException {
+            thread_id: read_u32(&self.data, rva)?,
+            code: read_u32(&self.data, record)?,
+            flags: read_u32(&self.data, record + 4)?,
+            address: read_u64(&self.data, record + 16)?,
+            parameters: (0..count)
+                .map(|i| read_u64(&self.data, record + 32 + i * 8))
+                .collect::<Result<_, _>>()?,
+            context: self.location(record + 152)?,
+        })
+    }
+
+    /// Parses a `MINIDUMP_SYSTEM_INFO`.
+    fn parse_system_info(&self, rva: usize) -> Result<MinidumpSystemInfo, DbgError> {
+        Ok(MinidumpSystemInfo {
+            processor_architecture: read_u16(&self.data, rva)?,
+            processor_level: read_u16(&self.data, rva + 2)?,
+            processor_revision: read_u16(&self.data, rva + 4)?,
+            number_of_processors: *self.data.get(rva + 6).ok_or(DbgError::InvalidMinidump("truncated stream"))?,
+            product_type: *self.data.get(rva + 7).ok_or(DbgError::InvalidMinidump("truncated stream"))?,
+            major_version: read_u32(&self.data, rva + 8)?,
+            minor_version: read_u32(&self.data, rva + 12)?,
+            build_number: read_u32(&self.data, rva + 16)?,
+            platform_id: read_u32(&self.data, rva + 20)?,
+        })
+    }
+}
+
+impl Memory for Minidump {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        Minidump::read_vaddr(self, vaddr, buffer)
+    }
+
+    fn pointer_width(&self) -> Result<PointerWidth, DbgError> {
+        match self.system_info.map(|info| info.processor_architecture) {
+            Some(PROCESSOR_ARCHITECTURE_INTEL | PROCESSOR_ARCHITECTURE_ARM) => Ok(PointerWidth::Bits32),
+            _ => Ok(PointerWidth::Bits64),
+        }
+    }
+}
+
+impl Registers for Minidump {
+    fn reg_indices(&self, names: &[&str]) -> Result<Vec<u32>, DbgError> {
+        Minidump::reg_indices(self, names)
+    }
+
+    fn reg_values(&self, indices: &[u32]) -> Result<Vec<Value>, DbgError> {
+        Minidump::reg_values(self, indices)
+    }
+}
+
+/// Reads a little-endian [`u16`] at `offset`.
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, DbgError> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or(DbgError::InvalidMinidump("truncated stream"))
+}
+
+/// Reads a little-endian [`u32`] at `offset`.
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, DbgError> {
+    data.get(offset..offset + 4)
+        .and_then(|b| b.try_into().ok())
+        .map(u32::from_le_bytes)
+        .ok_or(DbgError::InvalidMinidump("truncated stream"))
+}
+
+/// Reads a little-endian [`u64`] at `offset`.
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, DbgError> {
+    data.get(offset..offset + 8)
+        .and_then(|b| b.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(DbgError::InvalidMinidump("truncated stream"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a minidump stream by stream.
+    #[derive(Default)]
+    struct DumpBuilder {
+        /// The stream types and their contents.
+        streams: Vec<(u32, Vec<u8>)>,
+
+        /// Blobs referenced by the streams, appended after them.
+        blobs: Vec<u8>,
+    }
+
+    impl DumpBuilder {
+        /// Adds a blob and returns its offset relative to the start of the blob area.
+        fn blob(&mut self, bytes: &[u8]) -> u32 {
+            let offset = self.blobs.len() as u32;
+            self.blobs.extend_from_slice(bytes);
+            offset
+        }
+
+        /// Lays out the header, the directory, the streams and the blobs.
+        ///
+        /// Streams refer to blobs through offsets relative to the blob area, patched by `fixup`.
+        fn build(self, fixups: &[(usize, usize)]) -> Vec<u8> {
+            let directory = 32;
+            let mut rva = directory + self.streams.len() * 12;
+            let mut data = Vec::new();
+            data.extend_from_slice(&MINIDUMP_SIGNATURE.to_le_bytes());
+            data.extend_from_slice(&0xA793u32.to_le_bytes());
+            data.extend_from_slice(&(self.streams.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(directory as u32).to_le_bytes());
+            data.resize(directory, 0);
+
+            let mut stream_rvas = Vec::new();
+            for (kind, bytes) in &self.streams {
+                data.extend_from_slice(&kind.to_le_bytes());
+                data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+                data.extend_from_slice(&(rva as u32).to_le_bytes());
+                stream_rvas.push(rva);
+                rva += bytes.len();
+            }
+
+            for (_, bytes) in &self.streams {
+                data.extend_from_slice(bytes);
+            }
+
+            // Turn blob-relative offsets inside streams into file offsets
+            let blobs = data.len();
+            for &(stream, offset) in fixups {
+                let at = stream_rvas[stream] + offset;
+                let relative = u32::from_le_bytes(data[at..at + 4].try_into().unwrap()) as usize;
+                data[at..at + 4].copy_from_slice(&((blobs + relative) as u32).to_le_bytes());
+            }
+
+            data.extend_from_slice(&self.blobs);
+            data
+        }
+    }
+
+    /// Encodes little-endian fields back to back.
+    fn fields(values: &[(u64, usize)]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|&(value, size)| value.to_le_bytes()[..size].to_vec())
+            .collect()
+    }
+
+    /// Builds an x64 dump with one module, one thread, an exception and two memory ranges.
+    fn sample() -> Vec<u8> {
+        let mut builder = DumpBuilder::default();
+
+        let memory = builder.blob(&[0x11, 0x22, 0x33, 0x44]);
+        let high = builder.blob(&[0xAA, 0xBB, 0xCC, 0xDD]);
+        let name = builder.blob(&{
+            let units = "C:\\app.exe".encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<_>>();
+            [fields(&[(units.len() as u64, 4)]), units].concat()
+        });
+
+        let mut context = vec![0u8; 0x4D0];
+        context[0xF8..0x100].copy_from_slice(&0x0001_4000_1000_u64.to_le_bytes());
+        context[0x98..0xA0].copy_from_slice(&0x7FF0_0000u64.to_le_bytes());
+        let context = builder.blob(&context);
+
+        let mut system_info = fields(&[(PROCESSOR_ARCHITECTURE_AMD64 as u64, 2), (6, 2), (0, 2), (4, 1), (1, 1)]);
+        system_info.extend(fields(&[(10, 4), (0, 4), (19045, 4), (2, 4)]));
+        system_info.resize(56, 0);
+        builder.streams.push((SYSTEM_INFO_STREAM, system_info));
+
+        let memory_list = fields(&[
+            (2, 4),
+            (0x1000, 8),
+            (4, 4),
+            (memory as u64, 4),
+            (u64::MAX - 3, 8),
+            (4, 4),
+            (high as u64, 4),
+        ]);
+        builder.streams.push((MEMORY_LIST_STREAM, memory_list));
+
+        let mut module = fields(&[
+            (1, 4),
+            (0x0001_4000_0000, 8),
+            (0x2000, 4),
+            (0xC0FFEE, 4),
+            (0x5F5E_0F00, 4),
+            (name as u64, 4),
+        ]);
+        module.resize(4 + 108, 0);
+        builder.streams.push((MODULE_LIST_STREAM, module));
+
+        let thread = fields(&[
+            (1, 4),
+            (0x1234, 4),
+            (0, 4),
+            (0x20, 4),
+            (8, 4),
+            (0x0007_FF01_0000, 8),
+            (0x7FF0_0000, 8),
+            (0, 4),
+            (0, 4),
+            (0x4D0, 4),
+            (context as u64, 4),
+        ]);
+        builder.streams.push((THREAD_LIST_STREAM, thread));
+
+        let mut exception = fields(&[
+            (0x1234, 4),
+            (0, 4),
+            (0xC000_0005, 4),
+            (0, 4),
+            (0, 8),
+            (0x0001_4000_1000, 8),
+            (2, 4),
+            (0, 4),
+        ]);
+        exception.extend(fields(&[(1, 8), (0xDEAD, 8)]));
+        exception.resize(8 + 152, 0);
+        exception.extend(fields(&[(0x4D0, 4), (context as u64, 4)]));
+        builder.streams.push((EXCEPTION_STREAM, exception));
+
+        let unloaded = fields(&[
+            (12, 4),
+            (24, 4),
+            (1, 4),
+            (0x0001_8000_0000, 8),
+            (0x1000, 4),
+            (0, 4),
+            (0, 4),
+            (name as u64, 4),
+        ]);
+        builder.streams.push((UNLOADED_MODULE_LIST_STREAM, unloaded));
+
+        builder.build(&[(1, 16), (1, 32), (2, 24), (3, 48), (4, 164), (5, 32)])
+    }
+
+    #[test]
+    fn parses_streams() {
+        let dump = Minidump::parse(sample()).unwrap();
+
+        let info = dump.system_info().unwrap();
+        assert_eq!(info.processor_architecture, PROCESSOR_ARCHITECTURE_AMD64);
+        assert_eq!(info.number_of_processors, 4);
+        assert_eq!(info.build_number, 19045);
+        assert_eq!(dump.pointer_width().unwrap(), PointerWidth::Bits64);
+
+        let module = &dump.modules().unwrap()[0];
+        assert_eq!(module.base, 0x0001_4000_0000);
+        assert_eq!(module.size, 0x2000);
+        assert_eq!(module.checksum, 0xC0FFEE);
+        assert_eq!(module.timestamp, 0x5F5E_0F00);
+        assert_eq!(module.module_name, "app");
+        assert_eq!(module.image_name, "app.exe");
+        assert_eq!(module.loaded_image_name, "C:\\app.exe");
+        assert!(!module.is_unloaded());
+
+        let unloaded = &dump.unloaded_modules().unwrap()[0];
+        assert_eq!(unloaded.base, 0x0001_8000_0000);
+        assert_eq!(unloaded.module_name, "app");
+        assert!(unloaded.is_unloaded());
+
+        let thread = &dump.threads()[0];
+        assert_eq!(thread.id, 0x1234);
+        assert_eq!(thread.priority, 8);
+        assert_eq!(thread.teb, 0x0007_FF01_0000);
+
+        let exception = dump.exception().unwrap();
+        assert_eq!(exception.code, 0xC000_0005);
+        assert_eq!(exception.address, 0x0001_4000_1000);
+        assert_eq!(exception.parameters, vec![1, 0xDEAD]);
+        assert_eq!(dump.current_thread().unwrap().id, 0x1234);
+    }
+
+    #[test]
+    fn reads_memory_and_registers() {
+        let dump = Minidump::parse(sample()).unwrap();
+
+        assert_eq!(dump.read_type_vaddr::<u32>(0x1000).unwrap(), 0x4433_2211);
+
+        let mut buffer = [0u8; 8];
+        assert_eq!(dump.read_vaddr(0x1002, &mut buffer).unwrap(), 2);
+        assert!(matches!(dump.read_vaddr(0x2000, &mut buffer), Err(DbgError::InvalidAddress(0x2000))));
+
+        let indices = dump.reg_indices(&["rip", "RSP"]).unwrap();
+        let values = dump.reg_values(&indices).unwrap();
+        assert_eq!(values, vec![Value::I64(0x0001_4000_1000), Value::I64(0x7FF0_0000)]);
+        assert!(matches!(dump.reg_indices(&["eax"]), Err(DbgError::RegisterNotFound(_))));
+    }
+
+    #[test]
+    fn reads_at_the_end_of_the_address_space() {
+        let dump = Minidump::parse(sample()).unwrap();
+
+        let mut buffer = [0u8; 8];
+        assert_eq!(dump.read_vaddr(u64::MAX - 3, &mut buffer).unwrap(), 4);
+        assert_eq!(buffer[..4], [0xAA, 0xBB, 0xCC, 0xDD]);
+    }
+
+    #[test]
+    fn rejects_bad_signature() {
+        let mut data = sample();
+        data[0] = 0;
+        assert!(matches!(Minidump::parse(data), Err(DbgError::InvalidMinidump("bad signature"))));
+        assert!(matches!(Minidump::parse(vec![0; 2]), Err(DbgError::InvalidMinidump(_))));
+    }
+}
@@ -0,0 +1,386 @@
+//! An in-memory target used to exercise code written against the [`Target`](crate::Target) traits
+//! without a live debugging session.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+};
+use crate::{
+    error::DbgError,
+    ptr::PointerWidth,
+    target::{Commands, Memory, Registers, Symbols, Types},
+    types::TypeInfo,
+    value::{Value, ValueType},
+};
+
+/// A fake target backed by memory regions, symbols, registers and expressions defined up front.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let target = MockTarget::new()
+///     .with_memory(0x1000, b"hello\0".to_vec())
+///     .with_symbol("app!greeting", 0x1000);
+///
+/// let addr = target.get_symbol_address("app!greeting")?;
+/// assert_eq!(target.read_cstr(addr)?, "hello");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockTarget {
+    /// Mapped memory regions, keyed by their base address.
+    memory: RefCell<BTreeMap<u64, Vec<u8>>>,
+
+    /// Symbol names and their addresses.
+    symbols: BTreeMap<String, u64>,
+
+    /// Register names and values, indexed by their position.
+    registers: Vec<(String, Value)>,
+
+    /// Results returned for evaluated expressions.
+    expressions: BTreeMap<String, Value>,
+
+    /// Every command passed to [`Commands::exec`], in order.
+    commands: RefCell<Vec<String>>,
+
+    /// The width reported by [`Memory::pointer_width`].
+    pointer_width: PointerWidth,
+
+    /// Type layouts, keyed by their name.
+    types: BTreeMap<String, TypeInfo>,
+
+    /// Bumped by writes, commands and [`MockTarget::resume`], see [`Memory::generation`].
+    generation: Cell<u64>,
+}
+
+impl MockTarget {
+    /// Creates an empty mock target.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps `bytes` at the virtual address `base`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The virtual address of the first byte.
+    /// * `bytes` - The contents of the region.
+    pub fn with_memory(mut self, base: u64, bytes: Vec<u8>) -> Self {
+        self.memory.get_mut().insert(base, bytes);
+        self
+    }
+
+    /// Defines a symbol at the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The symbol name, e.g. `nt!PsInitialSystemProcess`.
+    /// * `addr` - The address of the symbol.
+    pub fn with_symbol<S>(mut self, name: S, addr: u64) -> Self
+    where
+        S: Into<String>,
+    {
+        self.symbols.insert(name.into(), addr);
+        self
+    }
+
+    /// Defines a register. Its index is the number of registers defined before it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The register name, e.g. `rip`.
+    /// * `value` - The register value.
+    pub fn with_register<S>(mut self, name: S, value: Value) -> Self
+    where
+        S: Into<String>,
+    {
+        self.registers.push((name.into(), value));
+        self
+    }
+
+    /// Defines the result of evaluating an expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression, matched verbatim.
+    /// * `value` - The value returned by [`Commands::evaluate`].
+    pub fn with_expression<S>(mut self, expr: S, value: Value) -> Self
+    where
+        S: Into<String>,
+    {
+        self.expressions.insert(expr.into(), value);
+        self
+    }
+
+    /// Sets the pointer width of the target, 64-bit by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width used by [`Memory::read_ptr`].
+    pub fn with_pointer_width(mut self, width: PointerWidth) -> Self {
+        self.pointer_width = width;
+        self
+    }
+
+    /// Defines the layout returned for a type, keyed by [`TypeInfo::name`].
+    ///
+    /// # Arguments
+    ///
+    /// * `info` - The layout of the type.
+    pub fn with_type(mut self, info: TypeInfo) -> Self {
+        self.types.insert(info.name.clone(), info);
+        self
+    }
+
+    /// Returns every command executed so far, in order.
+    pub fn commands(&self) -> Vec<String> {
+        self.commands.borrow().clone()
+    }
+
+    /// Writes a buffer to mapped memory, like `Dbg::write_vaddr`.
+    ///
+    /// Fails with [`DbgError::PartialWrite`] if the buffer runs into unmapped memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual address to write to.
+    /// * `buffer` - The bytes to write.
+    pub fn write_vaddr(&self, vaddr: u64, buffer: &[u8]) -> Result<(), DbgError> {
+        self.resume();
+        let mut memory = self.memory.borrow_mut();
+        let mut written = 0;
+
+        // Copy across adjacent regions until the buffer is written or unmapped memory is hit
+        while written < buffer.len() {
+            let Some((bytes, offset)) = region(&mut memory, vaddr.wrapping_add(written as u64)) else {
+                break;
+            };
+
+            let len = (bytes.len() - offset).min(buffer.len() - written);
+            bytes[offset..offset + len].copy_from_slice(&buffer[written..written + len]);
+            written += len;
+        }
+
+        match written {
+            0 if !buffer.is_empty() => Err(DbgError::InvalidAddress(vaddr)),
+            n if n < buffer.len() => Err(DbgError::PartialWrite {
+                requested: buffer.len(),
+                written: n,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Simulates the target running, bumping [`Memory::generation`] like a resumed `Dbg` does.
+    pub fn resume(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
+}
+
+/// Finds the region containing `vaddr`, returning it along with the offset of `vaddr` inside it.
+fn region(memory: &mut BTreeMap<u64, Vec<u8>>, vaddr: u64) -> Option<(&mut [u8], usize)> {
+    let (base, bytes) = memory.range_mut(..=vaddr).next_back()?;
+    let offset = usize::try_from(vaddr - base).ok()?;
+    (offset < bytes.len()).then_some((bytes.as_mut_slice(), offset))
+}
+
+impl Memory for MockTarget {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        let mut memory = self.memory.borrow_mut();
+        let mut read = 0;
+
+        // Copy across adjacent regions until the buffer is full or unmapped memory is hit
+        while read < buffer.len() {
+            let Some((bytes, offset)) = region(&mut memory, vaddr.wrapping_add(read as u64)) else {
+                break;
+            };
+
+            let len = (bytes.len() - offset).min(buffer.len() - read);
+            buffer[read..read + len].copy_from_slice(&bytes[offset..offset + len]);
+            read += len;
+        }
+
+        if read == 0 && !buffer.is_empty() {
+            return Err(DbgError::InvalidAddress(vaddr));
+        }
+
+        Ok(read)
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    fn pointer_width(&self) -> Result<PointerWidth, DbgError> {
+        Ok(self.pointer_width)
+    }
+}
+
+impl Symbols for MockTarget {
+    fn get_symbol_address(&self, name: &str) -> Result<u64, DbgError> {
+        self.symbols
+            .get(name)
+            .copied()
+            .ok_or_else(|| DbgError::SymbolNotFound(name.to_string()))
+    }
+
+    fn get_symbol_name(&self, addr: u64) -> Result<String, DbgError> {
+        // Pick the closest symbol at or below the address, like `GetNameByOffset`
+        self.symbols
+            .iter()
+            .filter(|&(_, &base)| base <= addr)
+            .max_by_key(|&(_, &base)| base)
+            .map(|(name, _)| name.clone())
+            .ok_or(DbgError::InvalidAddress(addr))
+    }
+}
+
+impl Types for MockTarget {
+    fn type_info(&self, name: &str) -> Result<TypeInfo, DbgError> {
+        self.types
+            .get(name)
+            .cloned()
+            .ok_or_else(|| DbgError::SymbolNotFound(name.to_string()))
+    }
+}
+
+impl Registers for MockTarget {
+    fn reg_indices(&self, names: &[&str]) -> Result<Vec<u32>, DbgError> {
+        names
+            .iter()
+            .map(|&n| {
+                self.registers
+                    .iter()
+                    .position(|(name, _)| name.eq_ignore_ascii_case(n))
+                    .map(|index| index as u32)
+                    .ok_or_else(|| DbgError::RegisterNotFound(n.to_string()))
+            })
+            .collect()
+    }
+
+    fn reg_values(&self, indices: &[u32]) -> Result<Vec<Value>, DbgError> {
+        indices
+            .iter()
+            .map(|&i| {
+                self.registers
+                    .get(i as usize)
+                    .map(|(_, value)| *value)
+                    .ok_or_else(|| DbgError::RegisterNotFound(i.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl Commands for MockTarget {
+    fn exec(&self, command: &str) -> Result<(), DbgError> {
+        // Like the engine, assume any command may resume the target or edit its memory
+        self.resume();
+        self.commands.borrow_mut().push(command.to_string());
+        Ok(())
+    }
+
+    fn evaluate(&self, expr: &str, _value_type: ValueType) -> Result<Value, DbgError> {
+        self.expressions
+            .get(expr)
+            .copied()
+            .ok_or_else(|| DbgError::InvalidExpression(expr.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ptr::PointerWidth;
+
+    #[test]
+    fn reads_across_adjacent_regions() {
+        let target = MockTarget::new()
+            .with_memory(0x1000, vec![1, 2, 3, 4])
+            .with_memory(0x1004, vec![5, 6, 7, 8]);
+
+        let mut buffer = [0u8; 6];
+        assert_eq!(target.read_vaddr(0x1002, &mut buffer).unwrap(), 6);
+        assert_eq!(buffer, [3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn stops_at_unmapped_memory() {
+        let target = MockTarget::new().with_memory(0x1000, vec![1, 2, 3, 4]);
+
+        let mut buffer = [0u8; 8];
+        assert_eq!(target.read_vaddr(0x1002, &mut buffer).unwrap(), 2);
+        assert!(matches!(target.read_vaddr(0x2000, &mut buffer), Err(DbgError::InvalidAddress(0x2000))));
+    }
+
+    #[test]
+    fn writes_bump_the_generation() {
+        let target = MockTarget::new()
+            .with_memory(0x1000, vec![0; 4])
+            .with_memory(0x1004, vec![0; 2]);
+        assert_eq!(target.generation(), 0);
+
+        target.write_vaddr(0x1002, &[1, 2, 3]).unwrap();
+        assert_eq!(target.read_array::<u8, 6>(0x1000).unwrap(), [0, 0, 1, 2, 3, 0]);
+        assert_eq!(target.generation(), 1);
+
+        assert!(matches!(
+            target.write_vaddr(0x1005, &[9, 9]),
+            Err(DbgError::PartialWrite { requested: 2, written: 1 })
+        ));
+        assert!(matches!(target.write_vaddr(0x2000, &[9]), Err(DbgError::InvalidAddress(0x2000))));
+        assert_eq!(target.read_array::<u8, 1>(0x1005).unwrap(), [9]);
+
+        target.exec("g").unwrap();
+        target.resume();
+        assert_eq!(target.generation(), 5);
+    }
+
+    #[test]
+    fn read_slice_reports_partial_reads() {
+        let target = MockTarget::new().with_memory(0x1000, vec![0xAA; 6]);
+
+        assert_eq!(target.read_slice::<u16>(0x1000, 3).unwrap(), vec![0xAAAA; 3]);
+        assert!(matches!(target.read_slice::<u32>(0x1000, 2), Err(DbgError::PartialRead { requested: 8, read: 6 })));
+    }
+
+    #[test]
+    fn read_array_reports_partial_reads() {
+        let target = MockTarget::new().with_memory(0x1000, 0x1122_3344_u32.to_le_bytes().to_vec());
+
+        assert_eq!(target.read_array::<u32, 1>(0x1000).unwrap(), [0x1122_3344]);
+        assert!(matches!(target.read_array::<u8, 8>(0x1000), Err(DbgError::PartialRead { requested: 8, read: 4 })));
+    }
+
+    #[test]
+    fn read_type_vaddr_reports_partial_reads() {
+        let target = MockTarget::new().with_memory(0x1000, 0x1122_3344_u32.to_le_bytes().to_vec());
+
+        assert_eq!(target.read_type_vaddr::<u32>(0x1000).unwrap(), 0x1122_3344);
+        assert!(matches!(
+            target.read_type_vaddr::<u64>(0x1000),
+            Err(DbgError::PartialRead { requested: 8, read: 4 })
+        ));
+    }
+
+    #[test]
+    fn read_ptr_honours_pointer_width() {
+        let bytes = 0x8877_6655_4433_2211_u64.to_le_bytes().to_vec();
+
+        let target = MockTarget::new().with_memory(0x1000, bytes.clone());
+        let ptr = target.read_ptr(0x1000).unwrap();
+        assert_eq!(ptr.addr(), 0x8877_6655_4433_2211);
+        assert_eq!(ptr.width(), PointerWidth::Bits64);
+
+        let target = MockTarget::new()
+            .with_memory(0x1000, bytes)
+            .with_pointer_width(PointerWidth::Bits32);
+        let ptr = target.read_ptr(0x1000).unwrap();
+        assert_eq!(ptr.addr(), 0x4433_2211);
+        assert_eq!(ptr.width(), PointerWidth::Bits32);
+    }
+
+    #[test]
+    fn read_ptr_reports_partial_reads() {
+        let target = MockTarget::new().with_memory(0x1000, vec![0; 4]);
+        assert!(matches!(target.read_ptr(0x1000), Err(DbgError::PartialRead { requested: 8, read: 4 })));
+    }
+}
@@ -0,0 +1,141 @@
+//! Types used to identify modules loaded in the target.
+
+/// Represents either a module address or a module name.
+///
+/// This enum is used to pass arguments to methods like `Dbg::remove_synthetic_module`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Module {
+    /// A module identified by its base address.
+    Address(u64),
+
+    /// A module identified by its name.
+    Name(String),
+}
+
+impl From<u64> for Module {
+    /// Converts a [`u64`] into a [`Module::Address`].
+    fn from(addr: u64) -> Self {
+        Module::Address(addr)
+    }
+}
+
+impl From<&str> for Module {
+    /// Converts a `&str` into a [`Module::Name`].
+    fn from(name: &str) -> Self {
+        Module::Name(name.to_string())
+    }
+}
+
+/// The kind of symbols loaded for a module, matching the `DEBUG_SYMTYPE_*` constants.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SymbolType {
+    /// No symbols are loaded.
+    #[default]
+    None = 0,
+
+    /// COFF symbols.
+    Coff = 1,
+
+    /// CodeView symbols.
+    CodeView = 2,
+
+    /// Symbols from a PDB file.
+    Pdb = 3,
+
+    /// Symbols generated from the export table.
+    Export = 4,
+
+    /// Loading was deferred until the symbols are needed.
+    Deferred = 5,
+
+    /// Symbols from a `.sym` file.
+    Sym = 6,
+
+    /// Symbols loaded through DIA.
+    Dia = 7,
+}
+
+impl TryFrom<u32> for SymbolType {
+    type Error = u32;
+
+    /// Converts a raw `DEBUG_SYMTYPE_*` value into a [`SymbolType`].
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => SymbolType::None,
+            1 => SymbolType::Coff,
+            2 => SymbolType::CodeView,
+            3 => SymbolType::Pdb,
+            4 => SymbolType::Export,
+            5 => SymbolType::Deferred,
+            6 => SymbolType::Sym,
+            7 => SymbolType::Dia,
+            other => return Err(other),
+        })
+    }
+}
+
+/// Describes a module known to the engine.
+///
+/// Returned by `Dbg::modules` and `Dbg::unloaded_modules`, and their `Minidump` counterparts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModuleInfo {
+    /// The base address of the image.
+    pub base: u64,
+
+    /// The size of the image in bytes.
+    pub size: u32,
+
+    /// The name the engine uses for the module in symbols, e.g. `ntdll`, empty if the engine
+    /// could not report it.
+    pub module_name: String,
+
+    /// The name of the image file, e.g. `ntdll.dll`, empty if the engine could not report it.
+    pub image_name: String,
+
+    /// The full path the image was loaded from, empty if the engine does not know it.
+    pub loaded_image_name: String,
+
+    /// The `TimeDateStamp` of the image.
+    pub timestamp: u32,
+
+    /// The checksum of the image.
+    pub checksum: u32,
+
+    /// The kind of symbols loaded for the module.
+    pub symbol_type: SymbolType,
+
+    /// The raw `DEBUG_MODULE_*` flags.
+    pub flags: u32,
+}
+
+impl ModuleInfo {
+    /// Returns the address just past the end of the image.
+    pub fn end(&self) -> u64 {
+        self.base.saturating_add(u64::from(self.size))
+    }
+
+    /// Returns whether `addr` lies inside the image.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to check.
+    pub fn contains(&self, addr: u64) -> bool {
+        (self.base..self.end()).contains(&addr)
+    }
+
+    /// Returns whether the module has been unloaded (`DEBUG_MODULE_UNLOADED`).
+    pub fn is_unloaded(&self) -> bool {
+        self.flags & 0x1 != 0
+    }
+
+    /// Returns whether the module is a user-mode module (`DEBUG_MODULE_USER_MODE`).
+    pub fn is_user_mode(&self) -> bool {
+        self.flags & 0x2 != 0
+    }
+
+    /// Returns whether the module is a synthetic module (`DEBUG_MODULE_SYNTHETIC`).
+    pub fn is_synthetic(&self) -> bool {
+        self.flags & 0x20 != 0
+    }
+}
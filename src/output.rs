@@ -0,0 +1,60 @@
+//! Capturing the text printed by debugger commands.
+
+#[cfg(windows)]
+use std::{cell::RefCell, rc::Rc};
+#[cfg(windows)]
+use windows::{
+    Win32::System::Diagnostics::Debug::Extensions::*,
+    core::{PCSTR, implement},
+};
+
+/// The output produced by a command, split by output mask.
+///
+/// Returned by `Dbg::exec_capture`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapturedOutput {
+    /// Text sent with `DEBUG_OUTPUT_NORMAL`.
+    pub normal: String,
+
+    /// Text sent with `DEBUG_OUTPUT_ERROR`.
+    pub error: String,
+
+    /// Text sent with `DEBUG_OUTPUT_WARNING`.
+    pub warning: String,
+
+    /// Text sent with `DEBUG_OUTPUT_VERBOSE`.
+    pub verbose: String,
+
+    /// Every piece of text in the order it was printed, whatever its mask.
+    pub all: String,
+}
+
+/// An `IDebugOutputCallbacks` implementation appending everything it receives to a [`CapturedOutput`].
+#[cfg(windows)]
+#[implement(IDebugOutputCallbacks)]
+pub(crate) struct OutputCapture {
+    /// The output collected so far, shared with the caller installing the callbacks.
+    pub(crate) captured: Rc<RefCell<CapturedOutput>>,
+}
+
+#[cfg(windows)]
+impl IDebugOutputCallbacks_Impl for OutputCapture_Impl {
+    fn Output(&self, mask: u32, text: &PCSTR) -> windows::core::Result<()> {
+        if text.is_null() {
+            return Ok(());
+        }
+
+        let text = String::from_utf8_lossy(unsafe { text.as_bytes() });
+        let mut captured = self.captured.borrow_mut();
+        match mask {
+            DEBUG_OUTPUT_NORMAL => captured.normal.push_str(&text),
+            DEBUG_OUTPUT_ERROR => captured.error.push_str(&text),
+            DEBUG_OUTPUT_WARNING => captured.warning.push_str(&text),
+            DEBUG_OUTPUT_VERBOSE => captured.verbose.push_str(&text),
+            _ => {}
+        }
+
+        captured.all.push_str(&text);
+        Ok(())
+    }
+}
@@ -0,0 +1,469 @@
+//! Software translation of virtual addresses through the page tables of a target.
+//!
+//! [`PageWalker`] reads the paging structures itself from any [`PhysicalMemory`], starting
+//! at a CR3 value, so translations do not depend on the process context selected in the
+//! debugger and also work on raw physical images.
+
+use crate::{
+    error::DbgError,
+    ptr::PointerWidth,
+    target::{Memory, PhysicalMemory},
+};
+
+/// Bits 12-51 of a paging-structure entry, holding the physical address it maps.
+const ADDRESS_MASK: u64 = 0x000F_FFFF_FFFF_F000;
+
+/// The entry maps a page or a table.
+const PRESENT: u64 = 1 << 0;
+
+/// Writes are allowed through the entry.
+const WRITABLE: u64 = 1 << 1;
+
+/// User-mode accesses are allowed through the entry.
+const USER: u64 = 1 << 2;
+
+/// The entry maps a large page instead of a table.
+const PAGE_SIZE: u64 = 1 << 7;
+
+/// Instruction fetches are not allowed through the entry.
+const NO_EXECUTE: u64 = 1 << 63;
+
+/// The paging mode used by the target processor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PagingMode {
+    /// 32-bit PAE paging: PDPT, page directory and page table.
+    X86Pae,
+
+    /// x64 4-level paging: PML4, PDPT, page directory and page table.
+    X64Level4,
+
+    /// x64 5-level paging (LA57): PML5, PML4, PDPT, page directory and page table.
+    X64Level5,
+}
+
+impl PagingMode {
+    /// Returns the levels walked by this mode, from the root table downwards.
+    fn levels(self) -> &'static [PageLevel] {
+        match self {
+            PagingMode::X86Pae => &[PageLevel::Pdpt, PageLevel::Pd, PageLevel::Pt],
+            PagingMode::X64Level4 => &[PageLevel::Pml4, PageLevel::Pdpt, PageLevel::Pd, PageLevel::Pt],
+            PagingMode::X64Level5 => &[PageLevel::Pml5, PageLevel::Pml4, PageLevel::Pdpt, PageLevel::Pd, PageLevel::Pt],
+        }
+    }
+
+    /// Returns the number of meaningful bits in a virtual address.
+    fn address_bits(self) -> u32 {
+        match self {
+            PagingMode::X86Pae => 32,
+            PagingMode::X64Level4 => 48,
+            PagingMode::X64Level5 => 57,
+        }
+    }
+}
+
+/// A level of the paging hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PageLevel {
+    /// The page table, mapping 4 KiB pages.
+    Pt,
+
+    /// The page directory, mapping page tables or 2 MiB pages.
+    Pd,
+
+    /// The page-directory-pointer table, mapping page directories or 1 GiB pages.
+    Pdpt,
+
+    /// The page-map level-4 table.
+    Pml4,
+
+    /// The page-map level-5 table.
+    Pml5,
+}
+
+impl PageLevel {
+    /// Returns the position of the first virtual address bit indexing this level.
+    fn shift(self) -> u32 {
+        match self {
+            PageLevel::Pt => 12,
+            PageLevel::Pd => 21,
+            PageLevel::Pdpt => 30,
+            PageLevel::Pml4 => 39,
+            PageLevel::Pml5 => 48,
+        }
+    }
+}
+
+/// The size of a mapped page.
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PageSize {
+    /// A 4 KiB page mapped by a page table.
+    Size4K = 0x1000,
+
+    /// A 2 MiB page mapped by a page directory.
+    Size2M = 0x20_0000,
+
+    /// A 1 GiB page mapped by a page-directory-pointer table.
+    Size1G = 0x4000_0000,
+}
+
+/// A paging-structure entry visited during a translation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageTableEntry {
+    /// The level of the table holding the entry.
+    pub level: PageLevel,
+
+    /// The physical address of the entry.
+    pub paddr: u64,
+
+    /// The raw value of the entry.
+    pub value: u64,
+}
+
+/// The result of translating a virtual address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Translation {
+    /// The translated virtual address.
+    pub vaddr: u64,
+
+    /// The physical address it maps to.
+    pub paddr: u64,
+
+    /// The size of the page containing the address.
+    pub page_size: PageSize,
+
+    /// Whether every level allows writes (R/W).
+    pub writable: bool,
+
+    /// Whether every level allows user-mode accesses (U/S).
+    pub user: bool,
+
+    /// Whether any level forbids instruction fetches (XD). Only meaningful when EFER.NXE is set.
+    pub no_execute: bool,
+
+    /// The entries visited, from the root table down to the one mapping the page.
+    pub entries: Vec<PageTableEntry>,
+}
+
+/// Translates virtual addresses by walking page tables in physical memory.
+///
+/// The walker also implements [`Memory`], reading virtual memory page by page through
+/// its own translations.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let cr3 = dbg.eval::<u64>("@cr3")?;
+/// let walker = PageWalker::new(&dbg, cr3, PagingMode::X64Level4);
+///
+/// let translation = walker.translate(0xFFFFF800_00000000)?;
+/// println!("{:#x} ({:?}, nx: {})", translation.paddr, translation.page_size, translation.no_execute);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PageWalker<'a, P: PhysicalMemory + ?Sized> {
+    /// The physical memory holding the page tables and the mapped pages.
+    memory: &'a P,
+
+    /// The physical address of the root table.
+    root: u64,
+
+    /// The paging mode used to interpret the tables.
+    mode: PagingMode,
+}
+
+impl<'a, P: PhysicalMemory + ?Sized> PageWalker<'a, P> {
+    /// Creates a walker for the address space described by a CR3 value.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The physical memory to read the paging structures from.
+    /// * `cr3` - The value of CR3. PCID and flag bits are ignored.
+    /// * `mode` - The paging mode of the target.
+    pub fn new(memory: &'a P, cr3: u64, mode: PagingMode) -> Self {
+        let root = match mode {
+            // The PDPT is only 32-byte aligned under PAE
+            PagingMode::X86Pae => cr3 & 0xFFFF_FFE0,
+            PagingMode::X64Level4 | PagingMode::X64Level5 => cr3 & ADDRESS_MASK,
+        };
+
+        Self { memory, root, mode }
+    }
+
+    /// Translates a virtual address into a physical address.
+    ///
+    /// Fails with [`DbgError::InvalidAddress`] for addresses outside the address space of
+    /// the paging mode, and with [`DbgError::PageNotPresent`] when a level is not present.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The virtual address to translate.
+    pub fn translate(&self, vaddr: u64) -> Result<Translation, DbgError> {
+        if !self.is_canonical(vaddr) {
+            return Err(DbgError::InvalidAddress(vaddr));
+        }
+
+        let mut translation = Translation {
+            vaddr,
+            paddr: 0,
+            page_size: PageSize::Size4K,
+            writable: true,
+            user: true,
+            no_execute: false,
+            entries: Vec::new(),
+        };
+
+        let mut table = self.root;
+        for &level in self.mode.levels() {
+            let index = (vaddr >> level.shift()) & 0x1FF;
+            let paddr = table + index * size_of::<u64>() as u64;
+            let value = self.read_entry(paddr)?;
+            translation.entries.push(PageTableEntry { level, paddr, value });
+
+            if value & PRESENT == 0 {
+                return Err(DbgError::PageNotPresent { vaddr, level });
+            }
+
+            // PAE PDPT entries have no access rights, those bits are reserved
+            if !(self.mode == PagingMode::X86Pae && level == PageLevel::Pdpt) {
+                translation.writable &= value & WRITABLE != 0;
+                translation.user &= value & USER != 0;
+                translation.no_execute |= value & NO_EXECUTE != 0;
+            }
+
+            let page_size = match level {
+                PageLevel::Pt => Some(PageSize::Size4K),
+                PageLevel::Pd if value & PAGE_SIZE != 0 => Some(PageSize::Size2M),
+                PageLevel::Pdpt if value & PAGE_SIZE != 0 && self.mode != PagingMode::X86Pae => Some(PageSize::Size1G),
+                _ => None,
+            };
+
+            if let Some(page_size) = page_size {
+                // Masking with the page size also drops the PAT bit of large page entries
+                let offset_mask = page_size as u64 - 1;
+                translation.paddr = (value & ADDRESS_MASK & !offset_mask) | (vaddr & offset_mask);
+                translation.page_size = page_size;
+                return Ok(translation);
+            }
+
+            table = value & ADDRESS_MASK;
+        }
+
+        unreachable!("the last paging level always maps a page")
+    }
+
+    /// Translates a virtual address, returning only the physical address.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The virtual address to translate.
+    pub fn virt_to_phys(&self, vaddr: u64) -> Result<u64, DbgError> {
+        Ok(self.translate(vaddr)?.paddr)
+    }
+
+    /// Checks that `vaddr` is a valid address for the paging mode.
+    fn is_canonical(&self, vaddr: u64) -> bool {
+        match self.mode {
+            PagingMode::X86Pae => vaddr <= u64::from(u32::MAX),
+            mode => {
+                // Every bit above the top translated bit must be a copy of it
+                let bits = mode.address_bits();
+                let extended = ((vaddr << (64 - bits)) as i64 >> (64 - bits)) as u64;
+                extended == vaddr
+            }
+        }
+    }
+
+    /// Reads a single paging-structure entry.
+    fn read_entry(&self, paddr: u64) -> Result<u64, DbgError> {
+        let mut buffer = [0u8; size_of::<u64>()];
+        if self.memory.read_paddr(paddr, &mut buffer)? != buffer.len() {
+            return Err(DbgError::InvalidAddress(paddr));
+        }
+
+        Ok(u64::from_le_bytes(buffer))
+    }
+}
+
+impl<P: PhysicalMemory + ?Sized> Memory for PageWalker<'_, P> {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        let mut read = 0;
+
+        // Translate each page separately, stopping at the first one that is not mapped
+        while read < buffer.len() {
+            let addr = vaddr.wrapping_add(read as u64);
+            let translation = match self.translate(addr) {
+                Ok(translation) => translation,
+                Err(err) if read == 0 => return Err(err),
+                Err(_) => break,
+            };
+
+            let remaining = translation.page_size as u64 - (addr & (translation.page_size as u64 - 1));
+            let len = (buffer.len() - read).min(remaining as usize);
+            let count = match self.memory.read_paddr(translation.paddr, &mut buffer[read..read + len]) {
+                Ok(count) => count,
+                Err(err) if read == 0 => return Err(err),
+                Err(_) => break,
+            };
+
+            read += count;
+            if count < len {
+                break;
+            }
+        }
+
+        Ok(read)
+    }
+    fn pointer_width(&self) -> Result<PointerWidth, DbgError> {
+        match self.mode {
+            PagingMode::X86Pae => Ok(PointerWidth::Bits32),
+            PagingMode::X64Level4 | PagingMode::X64Level5 => Ok(PointerWidth::Bits64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flags of a present, writable, user-mode entry.
+    const RWU: u64 = PRESENT | WRITABLE | USER;
+
+    /// Writes the entry indexing `vaddr` at `level` into the table at `table`.
+    fn map(memory: &mut [u8], table: u64, level: PageLevel, vaddr: u64, entry: u64) {
+        let paddr = (table + ((vaddr >> level.shift()) & 0x1FF) * 8) as usize;
+        memory[paddr..paddr + 8].copy_from_slice(&entry.to_le_bytes());
+    }
+
+    /// Builds 4-level tables at 0x1000-0x4000 mapping `vaddr` to the 4 KiB page at 0x5000.
+    fn tables_4_level(vaddr: u64) -> Vec<u8> {
+        let mut memory = vec![0u8; 0x8000];
+        map(&mut memory, 0x1000, PageLevel::Pml4, vaddr, 0x2000 | RWU);
+        map(&mut memory, 0x2000, PageLevel::Pdpt, vaddr, 0x3000 | RWU);
+        map(&mut memory, 0x3000, PageLevel::Pd, vaddr, 0x4000 | PRESENT | WRITABLE);
+        map(&mut memory, 0x4000, PageLevel::Pt, vaddr, 0x5000 | RWU | NO_EXECUTE);
+        memory
+    }
+
+    #[test]
+    fn walks_4_level_tables() {
+        let vaddr = 0xFFFF_F800_0012_3456;
+        let mut memory = tables_4_level(vaddr);
+        memory[0x5456..0x545B].copy_from_slice(b"hello");
+
+        // PCID bits in CR3 are ignored
+        let walker = PageWalker::new(memory.as_slice(), 0x1000 | 0x5, PagingMode::X64Level4);
+        let translation = walker.translate(vaddr).unwrap();
+        assert_eq!(translation.paddr, 0x5456);
+        assert_eq!(translation.page_size, PageSize::Size4K);
+        assert!(translation.writable);
+        assert!(!translation.user);
+        assert!(translation.no_execute);
+
+        let levels = translation.entries.iter().map(|entry| entry.level).collect::<Vec<_>>();
+        assert_eq!(levels, [PageLevel::Pml4, PageLevel::Pdpt, PageLevel::Pd, PageLevel::Pt]);
+        assert_eq!(translation.entries[3].paddr, 0x4000 + 0x123 * 8);
+
+        let mut buffer = [0u8; 5];
+        assert_eq!(walker.read_vaddr(vaddr, &mut buffer).unwrap(), 5);
+        assert_eq!(&buffer, b"hello");
+        assert_eq!(walker.pointer_width().unwrap(), PointerWidth::Bits64);
+
+        // Addresses outside the 48-bit canonical range are rejected before any read
+        assert!(matches!(walker.translate(0x0000_8000_0000_0000), Err(DbgError::InvalidAddress(_))));
+    }
+
+    #[test]
+    fn maps_large_pages() {
+        let vaddr = 0x0000_7FF6_1234_5678;
+        let mut memory = tables_4_level(vaddr);
+
+        // A 2 MiB page, with the PAT bit (12) set in the entry
+        map(&mut memory, 0x3000, PageLevel::Pd, vaddr, 0x8000_0000 | 1 << 12 | PAGE_SIZE | RWU);
+        let walker = PageWalker::new(memory.as_slice(), 0x1000, PagingMode::X64Level4);
+        let translation = walker.translate(vaddr).unwrap();
+        assert_eq!(translation.page_size, PageSize::Size2M);
+        assert_eq!(translation.paddr, 0x8000_0000 | (vaddr & 0x1F_FFFF));
+        assert_eq!(translation.entries.len(), 3);
+
+        // A 1 GiB page
+        map(&mut memory, 0x2000, PageLevel::Pdpt, vaddr, 0x1_4000_0000 | PAGE_SIZE | RWU);
+        let walker = PageWalker::new(memory.as_slice(), 0x1000, PagingMode::X64Level4);
+        let translation = walker.translate(vaddr).unwrap();
+        assert_eq!(translation.page_size, PageSize::Size1G);
+        assert_eq!(translation.paddr, 0x1_4000_0000 | (vaddr & 0x3FFF_FFFF));
+        assert_eq!(translation.entries.len(), 2);
+    }
+
+    #[test]
+    fn walks_5_level_tables() {
+        let vaddr = 0xFF12_3456_7890_1234;
+        let mut memory = vec![0u8; 0x8000];
+        map(&mut memory, 0x6000, PageLevel::Pml5, vaddr, 0x1000 | RWU);
+        map(&mut memory, 0x1000, PageLevel::Pml4, vaddr, 0x2000 | RWU);
+        map(&mut memory, 0x2000, PageLevel::Pdpt, vaddr, 0x3000 | RWU);
+        map(&mut memory, 0x3000, PageLevel::Pd, vaddr, 0x4000 | RWU);
+        map(&mut memory, 0x4000, PageLevel::Pt, vaddr, 0x5000 | RWU);
+
+        let walker = PageWalker::new(memory.as_slice(), 0x6000, PagingMode::X64Level5);
+        let translation = walker.translate(vaddr).unwrap();
+        assert_eq!(translation.paddr, 0x5234);
+        assert_eq!(translation.entries.len(), 5);
+        assert!(translation.user && !translation.no_execute);
+
+        // The same address is not canonical with 4 levels
+        let walker = PageWalker::new(memory.as_slice(), 0x1000, PagingMode::X64Level4);
+        assert!(matches!(walker.translate(vaddr), Err(DbgError::InvalidAddress(_))));
+    }
+
+    #[test]
+    fn walks_pae_tables() {
+        let vaddr = 0xC012_3456;
+        let mut memory = vec![0u8; 0x8000];
+
+        // The PDPT is 32-byte aligned and its entries carry no access rights
+        let pdpt = 0x1020;
+        let entry = pdpt + (vaddr >> 30) as usize * 8;
+        memory[entry..entry + 8].copy_from_slice(&(0x3000 | PRESENT).to_le_bytes());
+        map(&mut memory, 0x3000, PageLevel::Pd, vaddr, 0x4000 | RWU);
+        map(&mut memory, 0x4000, PageLevel::Pt, vaddr, 0x5000 | PRESENT | USER);
+
+        let walker = PageWalker::new(memory.as_slice(), pdpt as u64 | 0x18, PagingMode::X86Pae);
+        let translation = walker.translate(vaddr).unwrap();
+        assert_eq!(translation.paddr, 0x5456);
+        assert!(translation.user && !translation.writable);
+        assert_eq!(translation.entries[0].paddr, entry as u64);
+        assert_eq!(walker.pointer_width().unwrap(), PointerWidth::Bits32);
+
+        // 2 MiB pages under PAE
+        map(&mut memory, 0x3000, PageLevel::Pd, vaddr, 0x60_0000 | PAGE_SIZE | RWU);
+        let walker = PageWalker::new(memory.as_slice(), pdpt as u64, PagingMode::X86Pae);
+        assert_eq!(walker.translate(vaddr).unwrap().paddr, 0x60_0000 | (vaddr & 0x1F_FFFF));
+
+        assert!(matches!(walker.translate(0x1_0000_0000), Err(DbgError::InvalidAddress(_))));
+    }
+
+    #[test]
+    fn reports_pages_not_present() {
+        let vaddr = 0x0000_0000_0040_0FFC;
+        let mut memory = tables_4_level(vaddr);
+        memory[0x5FFC..0x6000].copy_from_slice(&[1, 2, 3, 4]);
+
+        // The next page is not mapped, so reads stop at the page boundary
+        let walker = PageWalker::new(memory.as_slice(), 0x1000, PagingMode::X64Level4);
+        let mut buffer = [0u8; 8];
+        assert_eq!(walker.read_vaddr(vaddr, &mut buffer).unwrap(), 4);
+        assert_eq!(buffer[..4], [1, 2, 3, 4]);
+        assert!(matches!(walker.translate(vaddr + 4), Err(DbgError::PageNotPresent { level: PageLevel::Pt, .. })));
+
+        map(&mut memory, 0x2000, PageLevel::Pdpt, vaddr, 0x3000 | WRITABLE);
+        let walker = PageWalker::new(memory.as_slice(), 0x1000, PagingMode::X64Level4);
+        assert!(matches!(
+            walker.translate(vaddr),
+            Err(DbgError::PageNotPresent {
+                vaddr: 0x40_0FFC,
+                level: PageLevel::Pdpt
+            })
+        ));
+        assert!(matches!(walker.read_vaddr(vaddr, &mut buffer), Err(DbgError::PageNotPresent { .. })));
+    }
+}
@@ -0,0 +1,267 @@
+//! Searching target memory for byte signatures with wildcards.
+
+use std::str::FromStr;
+use crate::{error::DbgError, target::Memory};
+
+/// Number of bytes read from the target at once while scanning.
+const CHUNK_SIZE: u64 = 0x10000;
+
+/// Granularity at which unreadable memory is skipped.
+const PAGE_SIZE: u64 = 0x1000;
+
+/// A byte signature where some positions match any byte.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let pattern = Pattern::from_ida("48 8B 05 ?? ?? ?? ?? E8")?;
+/// for addr in pattern.scan(&dbg, 0xFFFFF800_00000000, 0x100000)? {
+///     dprintln!(dbg, "match at {addr:#x}");
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    /// The expected bytes, ignored where `mask` is `false`.
+    bytes: Vec<u8>,
+
+    /// Whether each byte must match exactly.
+    mask: Vec<bool>,
+}
+
+impl Pattern {
+    /// Parses an IDA-style signature, e.g. `48 8B ?? ?? E8`.
+    ///
+    /// Bytes are separated by whitespace and wildcards are written `?` or `??`.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - The signature to parse.
+    pub fn from_ida(signature: &str) -> Result<Self, DbgError> {
+        let (bytes, mask) = signature
+            .split_whitespace()
+            .map(|token| match token {
+                "?" | "??" => Ok((0, false)),
+                hex if hex.len() == 2 => u8::from_str_radix(hex, 16)
+                    .map(|byte| (byte, true))
+                    .map_err(|_| DbgError::InvalidPattern(signature.to_string())),
+                _ => Err(DbgError::InvalidPattern(signature.to_string())),
+            })
+            .collect::<Result<(Vec<_>, Vec<_>), _>>()?;
+
+        Self::new(bytes, mask, signature)
+    }
+
+    /// Builds a signature from raw bytes and a code-style mask, e.g. `xx??x`.
+    ///
+    /// An `x` in the mask requires the byte at that position to match and a `?` accepts any byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The expected bytes.
+    /// * `mask` - One `x` or `?` per byte.
+    pub fn from_mask(bytes: &[u8], mask: &str) -> Result<Self, DbgError> {
+        if mask.len() != bytes.len() {
+            return Err(DbgError::InvalidPattern(mask.to_string()));
+        }
+
+        let exact = mask
+            .chars()
+            .map(|c| match c {
+                'x' | 'X' => Ok(true),
+                '?' => Ok(false),
+                _ => Err(DbgError::InvalidPattern(mask.to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(bytes.to_vec(), exact, mask)
+    }
+
+    /// Checks that the signature has at least one exact byte.
+    fn new(bytes: Vec<u8>, mask: Vec<bool>, source: &str) -> Result<Self, DbgError> {
+        // A signature of wildcards only would match everywhere
+        if !mask.contains(&true) {
+            return Err(DbgError::InvalidPattern(source.to_string()));
+        }
+
+        Ok(Self { bytes, mask })
+    }
+
+    /// Returns the length of the signature in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns whether the signature is empty, which is never the case once built.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns whether `data` starts with the signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The bytes to compare.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() >= self.len()
+            && self
+                .bytes
+                .iter()
+                .zip(&self.mask)
+                .zip(data)
+                .all(|((&expected, &exact), &byte)| !exact || expected == byte)
+    }
+
+    /// Returns the offsets of every match fully contained in `data`.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The bytes to search.
+    pub fn find_all(&self, data: &[u8]) -> Vec<usize> {
+        (0..data.len().saturating_sub(self.len() - 1))
+            .filter(|&offset| self.matches(&data[offset..]))
+            .collect()
+    }
+
+    /// Searches a range of virtual memory, returning the address of every match.
+    ///
+    /// Memory is read in chunks, matches spanning two chunks are found, and pages that
+    /// cannot be read are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory to search.
+    /// * `start` - The first address of the range.
+    /// * `size` - The size of the range in bytes.
+    pub fn scan<M>(&self, memory: &M, start: u64, size: u64) -> Result<Vec<u64>, DbgError>
+    where
+        M: Memory + ?Sized,
+    {
+        let end = start.checked_add(size).ok_or(DbgError::InvalidAddress(start))?;
+        let mut matches = Vec::new();
+
+        // Bytes at the end of the previous chunk, which may hold the start of a match
+        let mut window = Vec::with_capacity(CHUNK_SIZE as usize + self.len());
+        let mut window_base = start;
+        let mut addr = start;
+
+        while addr < end {
+            // Keep chunks page-aligned so that an unreadable page is skipped on its own
+            let chunk_end = end.min((addr & !(PAGE_SIZE - 1)).saturating_add(CHUNK_SIZE));
+            let mut buffer = vec![0u8; (chunk_end - addr) as usize];
+
+            let read = memory.read_vaddr(addr, &mut buffer).unwrap_or(0);
+            if read == 0 {
+                window.clear();
+                addr = (addr | (PAGE_SIZE - 1)).saturating_add(1).min(end);
+                window_base = addr;
+                continue;
+            }
+
+            window.extend_from_slice(&buffer[..read]);
+            matches.extend(self.find_all(&window).into_iter().map(|offset| window_base + offset as u64));
+
+            // Only `len - 1` bytes are carried over, so no match is reported twice
+            let keep = window.len().min(self.len() - 1);
+            window.drain(..window.len() - keep);
+            addr += read as u64;
+            window_base = addr - keep as u64;
+        }
+
+        Ok(matches)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = DbgError;
+
+    /// Parses an IDA-style signature, see [`Pattern::from_ida`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_ida(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ida_signatures() {
+        let pattern = Pattern::from_ida("48 8b ? ?? E8").unwrap();
+        assert_eq!(pattern.len(), 5);
+        assert!(pattern.matches(&[0x48, 0x8B, 0x00, 0xFF, 0xE8, 0x99]));
+        assert!(!pattern.matches(&[0x48, 0x8B, 0x00, 0xFF, 0xE9]));
+        assert!(!pattern.matches(&[0x48, 0x8B, 0x00, 0xFF]));
+        assert_eq!("48 ?? E8".parse::<Pattern>().unwrap(), Pattern::from_ida("48 ? E8").unwrap());
+    }
+
+    #[test]
+    fn rejects_bad_signatures() {
+        for signature in ["4", "GG", "123", "48 ?x", "?? ?", "", "   "] {
+            assert!(
+                matches!(Pattern::from_ida(signature), Err(DbgError::InvalidPattern(s)) if s == signature),
+                "{signature:?} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn builds_from_masks() {
+        let pattern = Pattern::from_mask(&[0x48, 0x00, 0xE8], "x?X").unwrap();
+        assert_eq!(pattern, Pattern::from_ida("48 ?? E8").unwrap());
+
+        assert!(matches!(Pattern::from_mask(&[0x48, 0x00], "x"), Err(DbgError::InvalidPattern(_))));
+        assert!(matches!(Pattern::from_mask(&[0x48], "y"), Err(DbgError::InvalidPattern(_))));
+        assert!(matches!(Pattern::from_mask(&[0x48, 0x00], "??"), Err(DbgError::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn finds_overlapping_matches() {
+        let pattern = Pattern::from_ida("AA ?? AA").unwrap();
+        assert_eq!(pattern.find_all(&[0xAA, 0xAA, 0xAA, 0xAA, 0x00, 0xAA]), vec![0, 1, 3]);
+        assert!(pattern.find_all(&[0xAA, 0x00]).is_empty());
+    }
+
+    #[cfg(feature = "mock")]
+    mod scan {
+        use super::*;
+        use crate::mock::MockTarget;
+
+        #[test]
+        fn finds_matches_across_chunks() {
+            let base = 0x0001_4000_0000;
+            let mut bytes = vec![0u8; 2 * CHUNK_SIZE as usize];
+            bytes[CHUNK_SIZE as usize - 2..CHUNK_SIZE as usize + 2].copy_from_slice(&[0xDE, 0xAD, 0x00, 0xEF]);
+            bytes[0x20..0x24].copy_from_slice(&[0xDE, 0xAD, 0x11, 0xEF]);
+            let target = MockTarget::new().with_memory(base, bytes);
+
+            let pattern = Pattern::from_ida("DE AD ? EF").unwrap();
+            assert_eq!(pattern.scan(&target, base, 2 * CHUNK_SIZE).unwrap(), vec![base + 0x20, base + CHUNK_SIZE - 2]);
+
+            // A range ending inside the match cannot contain it
+            assert_eq!(pattern.scan(&target, base, CHUNK_SIZE + 1).unwrap(), vec![base + 0x20]);
+        }
+
+        #[test]
+        fn skips_unreadable_pages() {
+            let mut low = vec![0u8; 0x2000];
+            low[0x10..0x12].copy_from_slice(&[0xCA, 0xFE]);
+            low[0x1FFF] = 0xCA;
+            let mut high = vec![0u8; 0x2000];
+            high[0] = 0xFE;
+            high[0x1FF0..0x1FF2].copy_from_slice(&[0xCA, 0xFE]);
+
+            // 0x3000 is a gap page, so the bytes around it must not form a match
+            let target = MockTarget::new().with_memory(0x1000, low).with_memory(0x4000, high);
+
+            let pattern = Pattern::from_ida("CA FE").unwrap();
+            assert_eq!(pattern.scan(&target, 0x1000, 0x5000).unwrap(), vec![0x1010, 0x5FF0]);
+            assert_eq!(pattern.scan(&target, 0x3000, 0x1000).unwrap(), Vec::<u64>::new());
+        }
+
+        #[test]
+        fn rejects_wrapping_ranges() {
+            let pattern = Pattern::from_ida("CA FE").unwrap();
+            assert!(matches!(pattern.scan(&MockTarget::new(), u64::MAX, 2), Err(DbgError::InvalidAddress(u64::MAX))));
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Types that can be safely read from raw target memory.
+
+/// A plain-old-data type, valid for every possible bit pattern.
+///
+/// Reads of [`Pod`] types can fill values straight from target memory, which is unsound for
+/// other `Copy` types such as `bool`, enums and references.
+///
+/// # Safety
+///
+/// Implementors must be inhabited by every bit pattern, including all zeroes,
+/// and must not contain padding bytes, pointers or references.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct ListEntry {
+///     flink: u64,
+///     blink: u64,
+/// }
+///
+/// unsafe impl Pod for ListEntry {}
+/// ```
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),*) => {
+        $(unsafe impl Pod for $ty {})*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// Views a slice of [`Pod`] values as its underlying bytes, so it can be written to memory.
+#[cfg(windows)]
+pub(crate) fn as_bytes<T: Pod>(values: &[T]) -> &[u8] {
+    // SAFETY: `T` has no padding, so every byte of the slice is initialized
+    unsafe { std::slice::from_raw_parts(values.as_ptr().cast(), size_of_val(values)) }
+}
+
+/// Views a slice of [`Pod`] values as its underlying bytes, so it can be filled from memory.
+pub(crate) fn as_bytes_mut<T: Pod>(values: &mut [T]) -> &mut [u8] {
+    // SAFETY: `T` has no padding and accepts any bit pattern, so its bytes can be freely written
+    unsafe { std::slice::from_raw_parts_mut(values.as_mut_ptr().cast(), size_of_val(values)) }
+}
@@ -0,0 +1,113 @@
+//! Pointers sized like the ones of the target, whatever the host architecture.
+
+use std::fmt;
+
+/// The size of a pointer on the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum PointerWidth {
+    /// 4-byte pointers, used by x86, ARM and WOW64 processes.
+    Bits32,
+
+    /// 8-byte pointers, used by x64 and ARM64.
+    #[default]
+    Bits64,
+}
+
+impl PointerWidth {
+    /// Returns the size of a pointer in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            PointerWidth::Bits32 => 4,
+            PointerWidth::Bits64 => 8,
+        }
+    }
+
+    /// Returns the mask of the bits a pointer can hold.
+    pub fn mask(self) -> u64 {
+        match self {
+            PointerWidth::Bits32 => u64::from(u32::MAX),
+            PointerWidth::Bits64 => u64::MAX,
+        }
+    }
+}
+
+/// A pointer read from the target, remembering its width.
+///
+/// Returned by [`Memory::read_ptr`](crate::Memory::read_ptr). Arithmetic wraps around at
+/// the width of the pointer, like it does on the target.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let peb = dbg.eval::<u64>("@$peb")?;
+/// let ldr = dbg.read_ptr(peb + 3 * dbg.pointer_width()?.size() as u64)?;
+/// if !ldr.is_null() {
+///     dprintln!(dbg, "Ldr at {ldr}");
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TargetPtr {
+    /// The address, truncated to `width`.
+    addr: u64,
+
+    /// The width of the pointer.
+    width: PointerWidth,
+}
+
+impl TargetPtr {
+    /// Creates a pointer, truncating `addr` to `width`.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address the pointer holds.
+    /// * `width` - The width of the pointer.
+    pub fn new(addr: u64, width: PointerWidth) -> Self {
+        Self {
+            addr: addr & width.mask(),
+            width,
+        }
+    }
+
+    /// Returns the address the pointer holds.
+    pub fn addr(self) -> u64 {
+        self.addr
+    }
+
+    /// Returns the width of the pointer.
+    pub fn width(self) -> PointerWidth {
+        self.width
+    }
+
+    /// Returns whether the pointer is null.
+    pub fn is_null(self) -> bool {
+        self.addr == 0
+    }
+
+    /// Returns the pointer moved by `offset` bytes, wrapping at its width.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The number of bytes to move by, possibly negative.
+    pub fn offset(self, offset: i64) -> Self {
+        Self::new(self.addr.wrapping_add_signed(offset), self.width)
+    }
+}
+
+impl From<TargetPtr> for u64 {
+    fn from(ptr: TargetPtr) -> Self {
+        ptr.addr
+    }
+}
+
+impl fmt::Display for TargetPtr {
+    /// Formats the pointer as zero-padded hexadecimal of its width, e.g. `0x7ffe0030`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#0width$x}", self.addr, width = self.width.size() * 2 + 2)
+    }
+}
+
+impl fmt::LowerHex for TargetPtr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.addr, f)
+    }
+}
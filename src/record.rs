@@ -0,0 +1,602 @@
+//! Recording and replaying the interaction with a target.
+//!
+//! [`Recorder`] wraps any target, such as a live [`Dbg`](crate::Dbg) session, and writes every
+//! call and its result to a file. [`Replay`] loads that file and answers the same calls without
+//! the original target, turning a session seen once into a deterministic regression test.
+//!
+//! Each line of a recording has the form `call<TAB>arguments<TAB>ok|err<TAB>result`.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+use crate::{
+    error::DbgError,
+    ptr::PointerWidth,
+    target::{Commands, Memory, Registers, Symbols, Types},
+    types::{FieldInfo, TypeInfo},
+    utils::{decode_hex, encode_hex, escape, unescape},
+    value::{Value, ValueType},
+};
+
+/// A target wrapper recording every call and its result.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let recorder = Recorder::create(dbg, "session.rec")?;
+/// let addr = recorder.get_symbol_address("nt!PsInitialSystemProcess")?;
+/// let process = recorder.read_type_vaddr::<u64>(addr)?;
+/// ```
+#[derive(Debug)]
+pub struct Recorder<T> {
+    /// The wrapped target answering the calls.
+    inner: T,
+
+    /// The file receiving the recorded calls.
+    writer: RefCell<BufWriter<File>>,
+}
+
+impl<T> Recorder<T> {
+    /// Wraps `inner`, recording its calls to a new file at `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The target answering the calls.
+    /// * `path` - The file to write the recording to. It is truncated if it exists.
+    pub fn create<P>(inner: T, path: P) -> Result<Self, DbgError>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self {
+            inner,
+            writer: RefCell::new(BufWriter::new(File::create(path)?)),
+        })
+    }
+
+    /// Returns a reference to the wrapped target.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Flushes the recording and returns the wrapped target.
+    pub fn into_inner(self) -> Result<T, DbgError> {
+        self.writer.into_inner().flush()?;
+        Ok(self.inner)
+    }
+
+    /// Appends a call and its result to the recording, then hands the result back.
+    fn record<R>(&self, call: &str, args: &str, result: Result<R, DbgError>, encode: impl FnOnce(&R) -> String) -> Result<R, DbgError> {
+        let (status, payload) = match &result {
+            Ok(value) => ("ok", encode(value)),
+            Err(err) => ("err", escape(&err.to_string())),
+        };
+
+        // Flush every line so the recording survives a crash of the debugger
+        let mut writer = self.writer.borrow_mut();
+        writeln!(writer, "{call}\t{args}\t{status}\t{payload}")?;
+        writer.flush()?;
+
+        result
+    }
+}
+
+impl<T: Memory> Memory for Recorder<T> {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        let result = self.inner.read_vaddr(vaddr, buffer);
+        self.record("read_vaddr", &format!("{vaddr:#x} {}", buffer.len()), result, |&read| encode_hex(&buffer[..read]))
+    }
+
+    fn read_cstr(&self, addr: u64) -> Result<String, DbgError> {
+        let result = self.inner.read_cstr(addr);
+        self.record("read_cstr", &format!("{addr:#x}"), result, |s| escape(s))
+    }
+
+    fn generation(&self) -> u64 {
+        // Record every answer so the replay sees the target resume at the same points
+        let generation = self.inner.generation();
+        self.record("generation", "", Ok(generation), u64::to_string)
+            .unwrap_or(generation)
+    }
+
+    fn pointer_width(&self) -> Result<PointerWidth, DbgError> {
+        let result = self.inner.pointer_width();
+        self.record("pointer_width", "", result, |width| (width.size() * 8).to_string())
+    }
+}
+
+impl<T: Symbols> Symbols for Recorder<T> {
+    fn get_symbol_address(&self, name: &str) -> Result<u64, DbgError> {
+        let result = self.inner.get_symbol_address(name);
+        self.record("get_symbol_address", &escape(name), result, |addr| format!("{addr:#x}"))
+    }
+
+    fn get_symbol_name(&self, addr: u64) -> Result<String, DbgError> {
+        let result = self.inner.get_symbol_name(addr);
+        self.record("get_symbol_name", &format!("{addr:#x}"), result, |s| escape(s))
+    }
+}
+
+impl<T: Registers> Registers for Recorder<T> {
+    fn reg_indices(&self, names: &[&str]) -> Result<Vec<u32>, DbgError> {
+        let result = self.inner.reg_indices(names);
+        self.record("reg_indices", &escape(&names.join(",")), result, |indices| join(indices))
+    }
+
+    fn reg_values(&self, indices: &[u32]) -> Result<Vec<Value>, DbgError> {
+        let result = self.inner.reg_values(indices);
+        self.record("reg_values", &join(indices), result, |values| {
+            values.iter().map(encode_value).collect::<Vec<_>>().join(",")
+        })
+    }
+}
+
+impl<T: Commands> Commands for Recorder<T> {
+    fn exec(&self, command: &str) -> Result<(), DbgError> {
+        let result = self.inner.exec(command);
+        self.record("exec", &escape(command), result, |_| String::new())
+    }
+
+    fn evaluate(&self, expr: &str, value_type: ValueType) -> Result<Value, DbgError> {
+        let result = self.inner.evaluate(expr, value_type);
+        self.record("evaluate", &format!("{} {}", value_type as u32, escape(expr)), result, encode_value)
+    }
+}
+
+impl<T: Types> Types for Recorder<T> {
+    fn type_info(&self, name: &str) -> Result<TypeInfo, DbgError> {
+        let result = self.inner.type_info(name);
+        self.record("type_info", &escape(name), result, encode_type_info)
+    }
+
+    fn field_offset(&self, type_name: &str, field: &str) -> Result<u32, DbgError> {
+        let result = self.inner.field_offset(type_name, field);
+        self.record("field_offset", &format!("{} {}", escape(type_name), escape(field)), result, u32::to_string)
+    }
+}
+
+/// Recorded answers for a call, either an encoded result or an error message.
+type Answers = VecDeque<Result<String, String>>;
+
+/// A target answering calls from a recording made by [`Recorder`].
+///
+/// Calls are matched by name and arguments. Repeated calls receive the recorded answers in
+/// order, and the last answer is served again once they run out. This includes
+/// [`Memory::generation`], so caches over a replay see the target resume where it did during
+/// the recording, and recordings without it report a target that never resumes.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let replay = Replay::open("session.rec")?;
+/// let addr = replay.get_symbol_address("nt!PsInitialSystemProcess")?;
+/// let process = replay.read_type_vaddr::<u64>(addr)?;
+/// ```
+#[derive(Debug, Default)]
+pub struct Replay {
+    /// Recorded answers, keyed by call name and arguments.
+    answers: RefCell<HashMap<(String, String), Answers>>,
+}
+
+impl Replay {
+    /// Loads a recording from a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file written by a [`Recorder`].
+    pub fn open<P>(path: P) -> Result<Self, DbgError>
+    where
+        P: AsRef<Path>,
+    {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Loads a recording from its contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `recording` - The text written by a [`Recorder`].
+    pub fn parse(recording: &str) -> Result<Self, DbgError> {
+        let mut answers = HashMap::<_, Answers>::new();
+        for (number, line) in recording.lines().enumerate().filter(|(_, line)| !line.is_empty()) {
+            let mut fields = line.splitn(4, '\t');
+            let (Some(call), Some(args), Some(status), Some(payload)) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+                return Err(DbgError::Replay(format!("malformed line {}", number + 1)));
+            };
+
+            let answer = match status {
+                "ok" => Ok(payload.to_string()),
+                "err" => Err(unescape(payload)),
+                _ => return Err(DbgError::Replay(format!("unknown status on line {}", number + 1))),
+            };
+
+            answers
+                .entry((call.to_string(), args.to_string()))
+                .or_default()
+                .push_back(answer);
+        }
+
+        Ok(Self {
+            answers: RefCell::new(answers),
+        })
+    }
+
+    /// Returns the next recorded answer for a call.
+    fn answer(&self, call: &str, args: &str) -> Result<String, DbgError> {
+        let mut answers = self.answers.borrow_mut();
+        let queue = answers
+            .get_mut(&(call.to_string(), args.to_string()))
+            .ok_or_else(|| DbgError::Replay(format!("no recorded answer for {call}({args})")))?;
+
+        // Keep the last answer around so it can be served to any further identical call
+        let answer = if queue.len() > 1 { queue.pop_front() } else { queue.front().cloned() };
+        match answer {
+            Some(Ok(payload)) => Ok(payload),
+            Some(Err(message)) => Err(DbgError::Recorded(message)),
+            None => Err(DbgError::Replay(format!("no recorded answer for {call}({args})"))),
+        }
+    }
+}
+
+impl Memory for Replay {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        let payload = self.answer("read_vaddr", &format!("{vaddr:#x} {}", buffer.len()))?;
+        let bytes = decode_hex(&payload).ok_or_else(|| invalid(&payload))?;
+        let read = bytes.len().min(buffer.len());
+        buffer[..read].copy_from_slice(&bytes[..read]);
+
+        Ok(read)
+    }
+
+    fn read_cstr(&self, addr: u64) -> Result<String, DbgError> {
+        self.answer("read_cstr", &format!("{addr:#x}")).map(|s| unescape(&s))
+    }
+
+    fn generation(&self) -> u64 {
+        self.answer("generation", "")
+            .ok()
+            .and_then(|payload| payload.parse().ok())
+            .unwrap_or(0)
+    }
+
+    fn pointer_width(&self) -> Result<PointerWidth, DbgError> {
+        match self.answer("pointer_width", "")?.as_str() {
+            "32" => Ok(PointerWidth::Bits32),
+            "64" => Ok(PointerWidth::Bits64),
+            payload => Err(invalid(payload)),
+        }
+    }
+}
+
+impl Symbols for Replay {
+    fn get_symbol_address(&self, name: &str) -> Result<u64, DbgError> {
+        let payload = self.answer("get_symbol_address", &escape(name))?;
+        parse_hex_u64(&payload).ok_or_else(|| invalid(&payload))
+    }
+
+    fn get_symbol_name(&self, addr: u64) -> Result<String, DbgError> {
+        self.answer("get_symbol_name", &format!("{addr:#x}")).map(|s| unescape(&s))
+    }
+}
+
+impl Registers for Replay {
+    fn reg_indices(&self, names: &[&str]) -> Result<Vec<u32>, DbgError> {
+        let payload = self.answer("reg_indices", &escape(&names.join(",")))?;
+        split(&payload)
+            .map(|index| index.parse().map_err(|_| invalid(&payload)))
+            .collect()
+    }
+
+    fn reg_values(&self, indices: &[u32]) -> Result<Vec<Value>, DbgError> {
+        let payload = self.answer("reg_values", &join(indices))?;
+        split(&payload).map(decode_value).collect()
+    }
+}
+
+impl Commands for Replay {
+    fn exec(&self, command: &str) -> Result<(), DbgError> {
+        self.answer("exec", &escape(command)).map(|_| ())
+    }
+
+    fn evaluate(&self, expr: &str, value_type: ValueType) -> Result<Value, DbgError> {
+        decode_value(&self.answer("evaluate", &format!("{} {}", value_type as u32, escape(expr)))?)
+    }
+}
+
+impl Types for Replay {
+    fn type_info(&self, name: &str) -> Result<TypeInfo, DbgError> {
+        decode_type_info(&self.answer("type_info", &escape(name))?)
+    }
+
+    fn field_offset(&self, type_name: &str, field: &str) -> Result<u32, DbgError> {
+        let payload = self.answer("field_offset", &format!("{} {}", escape(type_name), escape(field)))?;
+        payload.parse().map_err(|_| invalid(&payload))
+    }
+}
+
+/// Builds the error returned when a recorded payload cannot be decoded.
+fn invalid(payload: &str) -> DbgError {
+    DbgError::Replay(format!("invalid recorded payload: {payload}"))
+}
+
+/// Joins numbers with commas.
+fn join(values: &[u32]) -> String {
+    values.iter().map(u32::to_string).collect::<Vec<_>>().join(",")
+}
+
+/// Splits a comma separated payload, yielding nothing for an empty one.
+fn split(payload: &str) -> impl Iterator<Item = &str> {
+    payload.split(',').filter(|s| !s.is_empty())
+}
+
+/// Parses a `0x` prefixed hexadecimal number.
+fn parse_hex_u64(s: &str) -> Option<u64> {
+    u64::from_str_radix(s.strip_prefix("0x")?, 16).ok()
+}
+
+/// Encodes a [`Value`] as its type number and little-endian bytes, e.g. `4:efbeadde00000000`.
+fn encode_value(value: &Value) -> String {
+    let bytes = match *value {
+        Value::I8(v) => v.to_le_bytes().to_vec(),
+        Value::I16(v) => v.to_le_bytes().to_vec(),
+        Value::I32(v) => v.to_le_bytes().to_vec(),
+        Value::I64(v) => v.to_le_bytes().to_vec(),
+        Value::F32(v) => v.to_le_bytes().to_vec(),
+        Value::F64(v) => v.to_le_bytes().to_vec(),
+        Value::Raw(_, raw) => raw.to_vec(),
+    };
+
+    format!("{}:{}", value.value_type() as u32, encode_hex(&bytes))
+}
+
+/// Decodes a [`Value`] encoded by [`encode_value`].
+fn decode_value(payload: &str) -> Result<Value, DbgError> {
+    let (kind, hex) = payload.split_once(':').ok_or_else(|| invalid(payload))?;
+    let value_type = kind
+        .parse::<u32>()
+        .ok()
+        .and_then(|kind| ValueType::try_from(kind).ok())
+        .ok_or_else(|| invalid(payload))?;
+
+    let bytes = decode_hex(hex).ok_or_else(|| invalid(payload))?;
+    let value = match value_type {
+        ValueType::Int8 => bytes.try_into().ok().map(|b| Value::I8(u8::from_le_bytes(b))),
+        ValueType::Int16 => bytes.try_into().ok().map(|b| Value::I16(u16::from_le_bytes(b))),
+        ValueType::Int32 => bytes.try_into().ok().map(|b| Value::I32(u32::from_le_bytes(b))),
+        ValueType::Int64 => bytes.try_into().ok().map(|b| Value::I64(u64::from_le_bytes(b))),
+        ValueType::Float32 => bytes.try_into().ok().map(|b| Value::F32(f32::from_le_bytes(b))),
+        ValueType::Float64 => bytes.try_into().ok().map(|b| Value::F64(f64::from_le_bytes(b))),
+        other => bytes.try_into().ok().map(|b| Value::Raw(other, b)),
+    };
+
+    value.ok_or_else(|| invalid(payload))
+}
+
+/// Encodes a [`TypeInfo`] as comma separated entries, the type first and then each field.
+///
+/// Entries have the form `name:number:type_id:size`, where the name is hex encoded and the number
+/// is the module base for the type and the offset for fields.
+fn encode_type_info(info: &TypeInfo) -> String {
+    let fields = info
+        .fields
+        .iter()
+        .map(|field| (&field.name, u64::from(field.offset), field.type_id, field.size));
+
+    std::iter::once((&info.name, info.module, info.type_id, info.size))
+        .chain(fields)
+        .map(|(name, number, type_id, size)| format!("{}:{number:x}:{type_id}:{size}", encode_hex(name.as_bytes())))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Decodes a [`TypeInfo`] encoded by [`encode_type_info`].
+fn decode_type_info(payload: &str) -> Result<TypeInfo, DbgError> {
+    let entry = |entry: &str| -> Option<(String, u64, u32, u32)> {
+        let mut parts = entry.split(':');
+        let name = String::from_utf8(decode_hex(parts.next()?)?).ok()?;
+        let number = u64::from_str_radix(parts.next()?, 16).ok()?;
+        let type_id = parts.next()?.parse().ok()?;
+        let size = parts.next()?.parse().ok()?;
+        Some((name, number, type_id, size))
+    };
+
+    let mut entries = payload.split(',');
+    let (name, module, type_id, size) = entries.next().and_then(entry).ok_or_else(|| invalid(payload))?;
+    let fields = entries
+        .map(|e| {
+            let (name, offset, type_id, size) = entry(e)?;
+            Some(FieldInfo {
+                name,
+                offset: u32::try_from(offset).ok()?,
+                type_id,
+                size,
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid(payload))?;
+
+    Ok(TypeInfo {
+        name,
+        module,
+        type_id,
+        size,
+        fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Returns a recording path unique to the test.
+    fn recording(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("dbg-rs-{}-{name}.rec", std::process::id()))
+    }
+
+    /// Memory with no contents whose generation is bumped by hand.
+    #[derive(Default)]
+    struct Resuming {
+        generation: Cell<u64>,
+    }
+
+    impl Memory for Resuming {
+        fn read_vaddr(&self, _vaddr: u64, _buffer: &mut [u8]) -> Result<usize, DbgError> {
+            Ok(0)
+        }
+
+        fn generation(&self) -> u64 {
+            self.generation.get()
+        }
+    }
+
+    /// Builds a target answering every kind of call.
+    #[cfg(feature = "mock")]
+    fn target() -> crate::mock::MockTarget {
+        crate::mock::MockTarget::new()
+            .with_memory(0x1000, b"hello\0\x78\x56\x34\x12".to_vec())
+            .with_symbol("app!Counter", 0x1006)
+            .with_register("rip", Value::I64(0x1000))
+            .with_expression("@$peb", Value::I64(0x2000))
+            .with_pointer_width(PointerWidth::Bits32)
+            .with_type(TypeInfo {
+                name: "app!_LIST\tENTRY".to_string(),
+                module: 0x1000,
+                type_id: 7,
+                size: 16,
+                fields: vec![
+                    FieldInfo {
+                        name: "Flink".to_string(),
+                        offset: 0,
+                        type_id: 8,
+                        size: 8,
+                    },
+                    FieldInfo {
+                        name: "Blink".to_string(),
+                        offset: 8,
+                        type_id: 8,
+                        size: 8,
+                    },
+                ],
+            })
+    }
+
+    /// Makes the same calls against any target, collecting their results.
+    #[cfg(feature = "mock")]
+    fn session<T: Memory + Symbols + Registers + Commands + Types>(target: &T) -> Vec<String> {
+        vec![
+            format!("{:?}", target.read_type_vaddr::<u32>(0x1006)),
+            format!("{:?}", target.read_vaddr(0x1008, &mut [0; 4])),
+            format!("{:?}", target.read_cstr(0x1000)),
+            format!("{:?}", target.pointer_width()),
+            format!("{:?}", target.get_symbol_address("app!Counter")),
+            format!("{:?}", target.get_symbol_name(0x1008)),
+            format!("{:?}", target.reg_values(&target.reg_indices(&["rip"]).unwrap())),
+            format!("{:?}", target.exec("g")),
+            format!("{:?}", target.evaluate("@$peb", ValueType::Int64)),
+            format!("{:?}", target.type_info("app!_LIST\tENTRY")),
+            format!("{:?}", target.field_offset("app!_LIST\tENTRY", "Blink")),
+            format!("{:?}", target.get_symbol_address("app!Missing").is_err()),
+        ]
+    }
+
+    #[test]
+    #[cfg(feature = "mock")]
+    fn replays_a_recorded_session() {
+        let path = recording("session");
+        let recorder = Recorder::create(target(), &path).unwrap();
+        let recorded = session(&recorder);
+        assert_eq!(recorder.into_inner().unwrap().commands(), vec!["g"]);
+
+        let replay = Replay::open(&path).unwrap();
+        assert_eq!(session(&replay), recorded);
+        fs::remove_file(&path).unwrap();
+
+        // Errors come back with the original message
+        assert!(matches!(
+            replay.get_symbol_address("app!Missing"),
+            Err(DbgError::Recorded(message)) if message == DbgError::SymbolNotFound("app!Missing".to_string()).to_string()
+        ));
+    }
+
+    #[test]
+    fn rejects_unrecorded_calls() {
+        let replay = Replay::parse("get_symbol_address\tapp!Counter\tok\t0x1006\n").unwrap();
+        assert_eq!(replay.get_symbol_address("app!Counter").unwrap(), 0x1006);
+        assert!(matches!(replay.get_symbol_address("app!Other"), Err(DbgError::Replay(_))));
+        assert!(matches!(replay.type_info("app!_LIST_ENTRY"), Err(DbgError::Replay(_))));
+
+        assert!(matches!(Replay::parse("read_vaddr\t0x1000 4\n"), Err(DbgError::Replay(_))));
+        assert!(matches!(Replay::parse("read_vaddr\t0x1000 4\tmaybe\t\n"), Err(DbgError::Replay(_))));
+    }
+
+    #[test]
+    fn serves_repeated_calls_in_order() {
+        let replay = Replay::parse("evaluate\t4 @$t0\tok\t4:0100000000000000\nevaluate\t4 @$t0\tok\t4:0200000000000000\n").unwrap();
+        assert_eq!(replay.evaluate("@$t0", ValueType::Int64).unwrap(), Value::I64(1));
+        assert_eq!(replay.evaluate("@$t0", ValueType::Int64).unwrap(), Value::I64(2));
+        assert_eq!(replay.evaluate("@$t0", ValueType::Int64).unwrap(), Value::I64(2));
+    }
+
+    #[test]
+    fn replays_generation_changes() {
+        let path = recording("generation");
+        let recorder = Recorder::create(Resuming::default(), &path).unwrap();
+        assert_eq!(recorder.generation(), 0);
+        recorder.inner().generation.set(3);
+        assert_eq!(recorder.generation(), 3);
+        recorder.into_inner().unwrap();
+
+        let replay = Replay::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(replay.generation(), 0);
+        assert_eq!(replay.generation(), 3);
+        assert_eq!(replay.generation(), 3);
+
+        // Older recordings describe a target that never resumes
+        assert_eq!(Replay::default().generation(), 0);
+    }
+
+    #[test]
+    fn round_trips_payloads() {
+        for value in [
+            Value::I8(0x7F),
+            Value::I16(0xBEEF),
+            Value::I32(0xDEAD_BEEF),
+            Value::I64(u64::MAX),
+            Value::F32(1.5),
+            Value::F64(-2.25),
+            Value::Raw(ValueType::Vector128, [0xAB; 24]),
+        ] {
+            assert_eq!(decode_value(&encode_value(&value)).unwrap(), value);
+        }
+
+        let info = TypeInfo {
+            name: "app!_A,B:C".to_string(),
+            module: 0x0001_4000_0000,
+            type_id: 3,
+            size: 8,
+            fields: vec![FieldInfo {
+                name: "Tab\tField".to_string(),
+                offset: 4,
+                type_id: 5,
+                size: 4,
+            }],
+        };
+        assert_eq!(decode_type_info(&encode_type_info(&info)).unwrap(), info);
+
+        assert!(decode_value("4:0102").is_err());
+        assert!(decode_value("99:00").is_err());
+        assert!(decode_type_info("zz:0:0:0").is_err());
+    }
+
+    #[test]
+    fn escapes_arguments() {
+        let replay = Replay::parse(&format!("exec\t{}\tok\t\nread_cstr\t0x10\tok\t{}\n", escape("r\t\nx"), escape("a\tb\\c"))).unwrap();
+        replay.exec("r\t\nx").unwrap();
+        assert_eq!(replay.read_cstr(0x10).unwrap(), "a\tb\\c");
+    }
+}
@@ -0,0 +1,344 @@
+//! Describing the virtual memory regions of a target.
+
+#[cfg(windows)]
+use windows::Win32::System::Memory::MEMORY_BASIC_INFORMATION64;
+#[cfg(windows)]
+use crate::{Dbg, dbg::E_NOINTERFACE, error::DbgError};
+
+/// The access protection of a region, decoded from the `PAGE_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protection {
+    /// `PAGE_NOACCESS`
+    NoAccess,
+
+    /// `PAGE_READONLY`
+    ReadOnly,
+
+    /// `PAGE_READWRITE`
+    ReadWrite,
+
+    /// `PAGE_WRITECOPY`
+    WriteCopy,
+
+    /// `PAGE_EXECUTE`
+    Execute,
+
+    /// `PAGE_EXECUTE_READ`
+    ExecuteRead,
+
+    /// `PAGE_EXECUTE_READWRITE`
+    ExecuteReadWrite,
+
+    /// `PAGE_EXECUTE_WRITECOPY`
+    ExecuteWriteCopy,
+}
+
+impl Protection {
+    /// Returns whether the memory can be read.
+    pub fn is_readable(self) -> bool {
+        !matches!(self, Protection::NoAccess | Protection::Execute)
+    }
+
+    /// Returns whether the memory can be written, including copy-on-write.
+    pub fn is_writable(self) -> bool {
+        matches!(
+            self,
+            Protection::ReadWrite | Protection::WriteCopy | Protection::ExecuteReadWrite | Protection::ExecuteWriteCopy
+        )
+    }
+
+    /// Returns whether the memory can be executed.
+    pub fn is_executable(self) -> bool {
+        matches!(
+            self,
+            Protection::Execute | Protection::ExecuteRead | Protection::ExecuteReadWrite | Protection::ExecuteWriteCopy
+        )
+    }
+}
+
+impl TryFrom<u32> for Protection {
+    type Error = u32;
+
+    /// Converts raw `PAGE_*` flags into a [`Protection`], ignoring the
+    /// `PAGE_GUARD`, `PAGE_NOCACHE` and `PAGE_WRITECOMBINE` modifiers.
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value & 0xFF {
+            0x01 => Protection::NoAccess,
+            0x02 => Protection::ReadOnly,
+            0x04 => Protection::ReadWrite,
+            0x08 => Protection::WriteCopy,
+            0x10 => Protection::Execute,
+            0x20 => Protection::ExecuteRead,
+            0x40 => Protection::ExecuteReadWrite,
+            0x80 => Protection::ExecuteWriteCopy,
+            _ => return Err(value),
+        })
+    }
+}
+
+/// The allocation state of a region, matching the `MEM_COMMIT`, `MEM_RESERVE` and `MEM_FREE` constants.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryState {
+    /// Physical storage has been allocated for the region.
+    Commit = 0x1000,
+
+    /// The address range is reserved without any storage.
+    Reserve = 0x2000,
+
+    /// The address range is free.
+    Free = 0x10000,
+}
+
+impl TryFrom<u32> for MemoryState {
+    type Error = u32;
+
+    /// Converts a raw `MEM_*` state into a [`MemoryState`].
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x1000 => MemoryState::Commit,
+            0x2000 => MemoryState::Reserve,
+            0x10000 => MemoryState::Free,
+            other => return Err(other),
+        })
+    }
+}
+
+/// The kind of pages in a region, matching the `MEM_IMAGE`, `MEM_MAPPED` and `MEM_PRIVATE` constants.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryType {
+    /// The pages are mapped into the view of an image section.
+    Image = 0x100_0000,
+
+    /// The pages are mapped into the view of a data section.
+    Mapped = 0x4_0000,
+
+    /// The pages are private to the process.
+    Private = 0x2_0000,
+}
+
+impl TryFrom<u32> for MemoryType {
+    type Error = u32;
+
+    /// Converts a raw `MEM_*` type into a [`MemoryType`].
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x100_0000 => MemoryType::Image,
+            0x4_0000 => MemoryType::Mapped,
+            0x2_0000 => MemoryType::Private,
+            other => return Err(other),
+        })
+    }
+}
+
+/// A range of pages sharing the same state, protection and type.
+///
+/// Returned by `Dbg::regions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MemoryRegion {
+    /// The base address of the region.
+    pub base: u64,
+
+    /// The size of the region in bytes.
+    pub size: u64,
+
+    /// The base address of the allocation the region belongs to.
+    pub allocation_base: u64,
+
+    /// The protection the allocation was created with, if any.
+    pub allocation_protection: Option<Protection>,
+
+    /// The allocation state of the region.
+    pub state: MemoryState,
+
+    /// The current protection, or `None` for reserved and free regions.
+    pub protection: Option<Protection>,
+
+    /// Whether the pages are guard pages (`PAGE_GUARD`).
+    pub guard: bool,
+
+    /// Whether the pages are not cached (`PAGE_NOCACHE`).
+    pub no_cache: bool,
+
+    /// The kind of pages, or `None` for free regions.
+    pub memory_type: Option<MemoryType>,
+}
+
+impl MemoryRegion {
+    /// Returns the address just past the end of the region.
+    pub fn end(&self) -> u64 {
+        self.base.saturating_add(self.size)
+    }
+
+    /// Returns whether `addr` lies inside the region.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to check.
+    pub fn contains(&self, addr: u64) -> bool {
+        (self.base..self.end()).contains(&addr)
+    }
+
+    /// Returns whether the region is committed and readable without tripping a guard page.
+    pub fn is_readable(&self) -> bool {
+        self.state == MemoryState::Commit && !self.guard && self.protection.is_some_and(Protection::is_readable)
+    }
+}
+
+#[cfg(windows)]
+impl From<&MEMORY_BASIC_INFORMATION64> for MemoryRegion {
+    fn from(info: &MEMORY_BASIC_INFORMATION64) -> Self {
+        Self {
+            base: info.BaseAddress,
+            size: info.RegionSize,
+            allocation_base: info.AllocationBase,
+            allocation_protection: Protection::try_from(info.AllocationProtect.0).ok(),
+            // Every region is in one of the three states, `Free` is the safest fallback
+            state: MemoryState::try_from(info.State.0).unwrap_or(MemoryState::Free),
+            protection: Protection::try_from(info.Protect.0).ok(),
+            guard: info.Protect.0 & 0x100 != 0,
+            no_cache: info.Protect.0 & 0x200 != 0,
+            memory_type: MemoryType::try_from(info.Type.0).ok(),
+        }
+    }
+}
+
+/// An iterator over the memory regions of the target, created by `Dbg::regions`.
+///
+/// Iteration ends when the engine reports no region at or above the next address. Any other
+/// failure is yielded once and ends the iteration.
+#[cfg(windows)]
+#[derive(Debug)]
+pub struct Regions<'a> {
+    /// The session being queried.
+    dbg: &'a Dbg,
+
+    /// The address the next query starts at, or `None` once the address space is exhausted.
+    next: Option<u64>,
+}
+
+#[cfg(windows)]
+impl<'a> Regions<'a> {
+    /// Creates an iterator starting at the lowest address.
+    pub(crate) fn new(dbg: &'a Dbg) -> Self {
+        Self { dbg, next: Some(0) }
+    }
+}
+
+#[cfg(windows)]
+impl Iterator for Regions<'_> {
+    type Item = Result<MemoryRegion, DbgError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let addr = self.next.take()?;
+
+        let mut info = MEMORY_BASIC_INFORMATION64::default();
+        match unsafe { self.dbg.dataspaces.QueryVirtual(addr, &mut info) } {
+            Ok(()) => {}
+            // `E_NOINTERFACE` is how the engine reports the end of the address space
+            Err(err) if err.code() == E_NOINTERFACE => return None,
+            Err(err) => return Some(Err(err.into())),
+        }
+
+        // A zero-sized or wrapping region would make the walk loop forever
+        let region = MemoryRegion::from(&info);
+        self.next = region.base.checked_add(region.size).filter(|&next| next > addr);
+
+        Some(Ok(region))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a committed region at `base` with the given protection.
+    fn region(base: u64, size: u64, protection: Option<Protection>) -> MemoryRegion {
+        MemoryRegion {
+            base,
+            size,
+            allocation_base: base,
+            allocation_protection: protection,
+            state: MemoryState::Commit,
+            protection,
+            guard: false,
+            no_cache: false,
+            memory_type: Some(MemoryType::Private),
+        }
+    }
+
+    #[test]
+    fn decodes_protection() {
+        assert_eq!(Protection::try_from(0x04), Ok(Protection::ReadWrite));
+        assert_eq!(Protection::try_from(0x20), Ok(Protection::ExecuteRead));
+
+        // PAGE_GUARD, PAGE_NOCACHE and PAGE_WRITECOMBINE are masked off
+        assert_eq!(Protection::try_from(0x104), Ok(Protection::ReadWrite));
+        assert_eq!(Protection::try_from(0x202), Ok(Protection::ReadOnly));
+        assert_eq!(Protection::try_from(0x440), Ok(Protection::ExecuteReadWrite));
+
+        assert_eq!(Protection::try_from(0), Err(0));
+        assert_eq!(Protection::try_from(0x106), Err(0x106));
+    }
+
+    #[test]
+    fn classifies_protection() {
+        assert!(Protection::ReadOnly.is_readable());
+        assert!(!Protection::Execute.is_readable());
+        assert!(!Protection::NoAccess.is_readable());
+
+        assert!(Protection::WriteCopy.is_writable());
+        assert!(!Protection::ExecuteRead.is_writable());
+
+        assert!(Protection::ExecuteWriteCopy.is_executable());
+        assert!(!Protection::ReadWrite.is_executable());
+    }
+
+    #[test]
+    fn decodes_state_and_type() {
+        assert_eq!(MemoryState::try_from(0x1000), Ok(MemoryState::Commit));
+        assert_eq!(MemoryState::try_from(0x2000), Ok(MemoryState::Reserve));
+        assert_eq!(MemoryState::try_from(0x10000), Ok(MemoryState::Free));
+        assert_eq!(MemoryState::try_from(0x3000), Err(0x3000));
+
+        assert_eq!(MemoryType::try_from(0x100_0000), Ok(MemoryType::Image));
+        assert_eq!(MemoryType::try_from(0x4_0000), Ok(MemoryType::Mapped));
+        assert_eq!(MemoryType::try_from(0x2_0000), Ok(MemoryType::Private));
+        assert_eq!(MemoryType::try_from(0), Err(0));
+    }
+
+    #[test]
+    fn checks_bounds() {
+        let r = region(0x1000, 0x2000, Some(Protection::ReadOnly));
+        assert_eq!(r.end(), 0x3000);
+        assert!(r.contains(0x1000));
+        assert!(r.contains(0x2FFF));
+        assert!(!r.contains(0x3000));
+        assert!(!r.contains(0xFFF));
+
+        // The end saturates instead of wrapping at the top of the address space
+        let top = region(u64::MAX - 0xFFF, 0x2000, Some(Protection::ReadOnly));
+        assert_eq!(top.end(), u64::MAX);
+        assert!(top.contains(u64::MAX - 1));
+    }
+
+    #[test]
+    fn checks_readability() {
+        assert!(region(0x1000, 0x1000, Some(Protection::ExecuteRead)).is_readable());
+        assert!(!region(0x1000, 0x1000, Some(Protection::NoAccess)).is_readable());
+        assert!(!region(0x1000, 0x1000, None).is_readable());
+
+        let guarded = MemoryRegion {
+            guard: true,
+            ..region(0x1000, 0x1000, Some(Protection::ReadWrite))
+        };
+        assert!(!guarded.is_readable());
+
+        let reserved = MemoryRegion {
+            state: MemoryState::Reserve,
+            ..region(0x1000, 0x1000, Some(Protection::ReadWrite))
+        };
+        assert!(!reserved.is_readable());
+    }
+}
@@ -0,0 +1,662 @@
+//! Capturing target memory and comparing captures taken at different times.
+//!
+//! A [`Snapshot`] owns a copy of the captured bytes, so it outlives the session it was taken
+//! from. Snapshots are saved as text, one record per line:
+//!
+//! * `module<TAB>base<TAB>size<TAB>name` for each module the snapshot knows about.
+//! * `range<TAB>base<TAB>label<TAB>bytes` for each captured range, with the bytes in hexadecimal.
+//!
+//! Backslashes, tabs and line breaks in names and labels are escaped with a backslash.
+
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+use crate::{
+    error::DbgError,
+    region::MemoryRegion,
+    target::{Memory, Symbols},
+    utils::{decode_hex, encode_hex, escape, unescape},
+};
+
+/// Number of bytes read from the target at once while capturing.
+const CHUNK_SIZE: u64 = 0x10000;
+
+/// Granularity at which unreadable memory is skipped.
+const PAGE_SIZE: u64 = 0x1000;
+
+/// A contiguous range of captured bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SnapshotRange {
+    /// The address of the first byte.
+    pub base: u64,
+
+    /// The captured bytes.
+    pub bytes: Vec<u8>,
+
+    /// What the range belongs to, e.g. `ntdll!.data` for a module section.
+    pub label: Option<String>,
+}
+
+impl SnapshotRange {
+    /// Returns the address just past the end of the range.
+    pub fn end(&self) -> u64 {
+        self.base.saturating_add(self.bytes.len() as u64)
+    }
+}
+
+/// A module recorded in a snapshot, used to name changed addresses offline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SnapshotModule {
+    /// The module name, e.g. `ntdll`.
+    pub name: String,
+
+    /// The base address of the image.
+    pub base: u64,
+
+    /// The size of the image in bytes.
+    pub size: u64,
+}
+
+/// A run of bytes that differs between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MemoryChange {
+    /// The address of the first changed byte.
+    pub addr: u64,
+
+    /// The bytes in the earlier snapshot.
+    pub old: Vec<u8>,
+
+    /// The bytes in the later snapshot.
+    pub new: Vec<u8>,
+
+    /// The address as `module+offset`, or a full symbol after [`MemoryChange::symbolize`].
+    pub symbol: Option<String>,
+}
+
+impl MemoryChange {
+    /// Returns the number of changed bytes.
+    pub fn len(&self) -> usize {
+        self.new.len()
+    }
+
+    /// Returns whether the change is empty, which is never the case for a diff result.
+    pub fn is_empty(&self) -> bool {
+        self.new.is_empty()
+    }
+
+    /// Replaces [`MemoryChange::symbol`] with the symbol the target reports for the address,
+    /// e.g. `ntdll!LdrpHeap+0x10`.
+    ///
+    /// The current name is kept if the target has no symbol for the address.
+    ///
+    /// # Arguments
+    ///
+    /// * `symbols` - The target resolving the address.
+    pub fn symbolize<S>(&mut self, symbols: &S)
+    where
+        S: Symbols + ?Sized,
+    {
+        if let Ok(name) = symbols.get_symbol_name(self.addr) {
+            self.symbol = Some(match symbols.get_symbol_address(&name) {
+                Ok(base) if base < self.addr => format!("{name}+{:#x}", self.addr - base),
+                _ => name,
+            });
+        }
+    }
+}
+
+/// An owned copy of parts of target memory.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let before = dbg.snapshot_module("ntdll")?;
+/// dbg.step_over()?;
+/// let after = dbg.snapshot_module("ntdll")?;
+///
+/// for change in before.diff(&after) {
+///     dprintln!(dbg, "{:?} {:#x}: {:02x?} -> {:02x?}", change.symbol, change.addr, change.old, change.new);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    /// The captured ranges, sorted by address.
+    ranges: Vec<SnapshotRange>,
+
+    /// The modules used to name changed addresses.
+    modules: Vec<SnapshotModule>,
+}
+
+impl Snapshot {
+    /// Creates an empty snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the captured ranges, sorted by address.
+    pub fn ranges(&self) -> &[SnapshotRange] {
+        &self.ranges
+    }
+
+    /// Returns the modules recorded in the snapshot.
+    pub fn modules(&self) -> &[SnapshotModule] {
+        &self.modules
+    }
+
+    /// Records a module, so that changes inside it are named `module+offset`.
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - The module to record.
+    pub fn add_module(&mut self, module: SnapshotModule) {
+        self.modules.push(module);
+    }
+
+    /// Captures a range of virtual memory.
+    ///
+    /// Pages that cannot be read are left out, splitting the range in several parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory to capture.
+    /// * `base` - The first address of the range.
+    /// * `size` - The size of the range in bytes.
+    /// * `label` - What the range belongs to, if known.
+    pub fn capture_range<M>(&mut self, memory: &M, base: u64, size: u64, label: Option<&str>) -> Result<(), DbgError>
+    where
+        M: Memory + ?Sized,
+    {
+        let end = base.checked_add(size).ok_or(DbgError::InvalidAddress(base))?;
+        let mut current: Option<SnapshotRange> = None;
+        let mut addr = base;
+
+        while addr < end {
+            // Keep chunks page-aligned so that an unreadable page is skipped on its own
+            let chunk_end = end.min((addr & !(PAGE_SIZE - 1)).saturating_add(CHUNK_SIZE));
+            let mut buffer = vec![0u8; (chunk_end - addr) as usize];
+
+            let read = memory.read_vaddr(addr, &mut buffer).unwrap_or(0);
+            if read == 0 {
+                self.insert(current.take());
+                addr = (addr | (PAGE_SIZE - 1)).saturating_add(1).min(end);
+                continue;
+            }
+
+            buffer.truncate(read);
+            match &mut current {
+                Some(range) if range.end() == addr => range.bytes.extend_from_slice(&buffer),
+                _ => {
+                    self.insert(current.take());
+                    current = Some(SnapshotRange {
+                        base: addr,
+                        bytes: buffer,
+                        label: label.map(str::to_string),
+                    });
+                }
+            }
+
+            addr += read as u64;
+        }
+
+        self.insert(current);
+        Ok(())
+    }
+
+    /// Captures every readable region among `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory to capture.
+    /// * `regions` - The regions to capture, e.g. from `Dbg::regions`.
+    pub fn capture_regions<M, I>(&mut self, memory: &M, regions: I) -> Result<(), DbgError>
+    where
+        M: Memory + ?Sized,
+        I: IntoIterator<Item = MemoryRegion>,
+    {
+        for region in regions.into_iter().filter(MemoryRegion::is_readable) {
+            self.capture_range(memory, region.base, region.size, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Captures the sections of a PE image mapped in memory and records the module.
+    ///
+    /// Each range is labelled `module!section`.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory to capture.
+    /// * `name` - The module name, e.g. `ntdll`.
+    /// * `base` - The base address of the image.
+    pub fn capture_module<M>(&mut self, memory: &M, name: &str, base: u64) -> Result<(), DbgError>
+    where
+        M: Memory + ?Sized,
+    {
+        let mut headers = vec![0u8; PAGE_SIZE as usize];
+        let read = memory.read_vaddr(base, &mut headers)?;
+        headers.truncate(read);
+
+        let image = ImageHeaders::parse(&headers).ok_or(DbgError::InvalidImage(base))?;
+        self.add_module(SnapshotModule {
+            name: name.to_string(),
+            base,
+            size: u64::from(image.size),
+        });
+
+        for section in image.sections {
+            let label = format!("{name}!{}", section.name);
+            let start = base
+                .checked_add(u64::from(section.virtual_address))
+                .ok_or(DbgError::InvalidImage(base))?;
+
+            self.capture_range(memory, start, u64::from(section.virtual_size), Some(&label))?;
+        }
+
+        Ok(())
+    }
+
+    /// Compares this snapshot with a later one and returns the changed byte runs.
+    ///
+    /// Only addresses captured in both snapshots are compared. Changes inside a recorded
+    /// module are named `module+offset`.
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - The later snapshot.
+    pub fn diff(&self, after: &Snapshot) -> Vec<MemoryChange> {
+        let mut changes: Vec<MemoryChange> = Vec::new();
+        for old in &self.ranges {
+            for new in after.ranges.iter().filter(|new| new.base < old.end() && old.base < new.end()) {
+                let start = old.base.max(new.base);
+                let end = old.end().min(new.end());
+                let old_bytes = &old.bytes[(start - old.base) as usize..(end - old.base) as usize];
+                let new_bytes = &new.bytes[(start - new.base) as usize..(end - new.base) as usize];
+
+                for (offset, (&before, &now)) in old_bytes.iter().zip(new_bytes).enumerate() {
+                    if before == now {
+                        continue;
+                    }
+
+                    let addr = start + offset as u64;
+                    match changes.last_mut() {
+                        Some(change) if change.addr + change.len() as u64 == addr => {
+                            change.old.push(before);
+                            change.new.push(now);
+                        }
+                        _ => changes.push(MemoryChange {
+                            addr,
+                            old: vec![before],
+                            new: vec![now],
+                            symbol: self.name_of(addr).or_else(|| after.name_of(addr)),
+                        }),
+                    }
+                }
+            }
+        }
+
+        changes
+    }
+
+    /// Writes the snapshot to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write. It is truncated if it exists.
+    pub fn save<P>(&self, path: P) -> Result<(), DbgError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the snapshot to any writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination of the text.
+    pub fn write_to<W>(&self, writer: &mut W) -> Result<(), DbgError>
+    where
+        W: Write,
+    {
+        for module in &self.modules {
+            writeln!(writer, "module\t{:x}\t{:x}\t{}", module.base, module.size, escape(&module.name))?;
+        }
+
+        for range in &self.ranges {
+            let label = escape(range.label.as_deref().unwrap_or_default());
+            writeln!(writer, "range\t{:x}\t{label}\t{}", range.base, encode_hex(&range.bytes))?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads a snapshot from a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file written by [`Snapshot::save`].
+    pub fn open<P>(path: P) -> Result<Self, DbgError>
+    where
+        P: AsRef<Path>,
+    {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Loads a snapshot from its contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text written by [`Snapshot::write_to`].
+    pub fn parse(text: &str) -> Result<Self, DbgError> {
+        let mut snapshot = Self::new();
+        for (number, line) in text.lines().enumerate().filter(|(_, line)| !line.is_empty()) {
+            let malformed = || DbgError::InvalidSnapshot(format!("malformed line {}", number + 1));
+            let fields = line.splitn(4, '\t').collect::<Vec<_>>();
+            let [kind, base, third, fourth] = fields[..] else {
+                return Err(malformed());
+            };
+
+            let base = u64::from_str_radix(base, 16).map_err(|_| malformed())?;
+            match kind {
+                "module" => snapshot.add_module(SnapshotModule {
+                    name: unescape(fourth),
+                    base,
+                    size: u64::from_str_radix(third, 16).map_err(|_| malformed())?,
+                }),
+                "range" => snapshot.insert(Some(SnapshotRange {
+                    base,
+                    bytes: decode_hex(fourth).ok_or_else(malformed)?,
+                    label: (!third.is_empty()).then(|| unescape(third)),
+                })),
+                _ => return Err(malformed()),
+            }
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Inserts a captured range, keeping the ranges sorted.
+    fn insert(&mut self, range: Option<SnapshotRange>) {
+        if let Some(range) = range.filter(|range| !range.bytes.is_empty()) {
+            let index = self.ranges.partition_point(|other| other.base <= range.base);
+            self.ranges.insert(index, range);
+        }
+    }
+
+    /// Names an address as `module+offset` if it lies in a recorded module.
+    fn name_of(&self, addr: u64) -> Option<String> {
+        self.modules
+            .iter()
+            .find(|module| (module.base..module.base.saturating_add(module.size)).contains(&addr))
+            .map(|module| format!("{}+{:#x}", module.name, addr - module.base))
+    }
+}
+
+/// A section of a PE image.
+struct Section {
+    /// The section name, e.g. `.data`.
+    name: String,
+
+    /// The offset of the section from the image base.
+    virtual_address: u32,
+
+    /// The size of the section in memory.
+    virtual_size: u32,
+}
+
+/// The parts of the PE headers needed to locate the sections.
+struct ImageHeaders {
+    /// `SizeOfImage` from the optional header.
+    size: u32,
+
+    /// The section table.
+    sections: Vec<Section>,
+}
+
+impl ImageHeaders {
+    /// Parses the headers at the start of a mapped image, returning `None` if they are malformed.
+    fn parse(headers: &[u8]) -> Option<Self> {
+        let u16_at = |offset: usize| Some(u16::from_le_bytes(headers.get(offset..offset + 2)?.try_into().ok()?));
+        let u32_at = |offset: usize| Some(u32::from_le_bytes(headers.get(offset..offset + 4)?.try_into().ok()?));
+
+        if headers.get(..2)? != b"MZ" {
+            return None;
+        }
+
+        let nt = u32_at(0x3C)? as usize;
+        if headers.get(nt..nt + 4)? != b"PE\0\0" {
+            return None;
+        }
+
+        // IMAGE_FILE_HEADER follows the signature, and SizeOfImage sits at the same offset
+        // in the 32 and 64-bit optional headers
+        let file_header = nt + 4;
+        let count = u16_at(file_header + 2)? as usize;
+        let optional_header = file_header + 20;
+        let size = u32_at(optional_header + 56)?;
+        let table = optional_header + u16_at(file_header + 16)? as usize;
+
+        let sections = (0..count)
+            .map(|index| {
+                let entry = headers.get(table + index * 40..table + (index + 1) * 40)?;
+                let name = &entry[..8];
+                let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+                Some(Section {
+                    name: String::from_utf8_lossy(&name[..len]).into_owned(),
+                    virtual_size: u32::from_le_bytes(entry[8..12].try_into().ok()?),
+                    virtual_address: u32::from_le_bytes(entry[12..16].try_into().ok()?),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { size, sections })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a snapshot with awkward names and labels.
+    fn sample() -> Snapshot {
+        let mut snapshot = Snapshot::new();
+        snapshot.add_module(SnapshotModule {
+            name: "C:\\apps\\tab\there.dll".to_string(),
+            base: 0x1_8000_0000,
+            size: 0x2000,
+        });
+
+        snapshot.insert(Some(SnapshotRange {
+            base: 0x1_8000_1000,
+            bytes: vec![0xDE, 0xAD, 0xBE, 0xEF],
+            label: Some("line\nbreak\r\\.data".to_string()),
+        }));
+
+        snapshot.insert(Some(SnapshotRange {
+            base: 0x1000,
+            bytes: vec![1, 2, 3],
+            label: None,
+        }));
+
+        snapshot
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let snapshot = sample();
+        let mut text = Vec::new();
+        snapshot.write_to(&mut text).unwrap();
+
+        let text = String::from_utf8(text).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.contains("\\t") && text.contains("\\n") && text.contains("\\\\"));
+        assert_eq!(Snapshot::parse(&text).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn round_trips_through_files() {
+        let path = std::env::temp_dir().join(format!("dbg-rs-{}-snapshot.txt", std::process::id()));
+        let snapshot = sample();
+        snapshot.save(&path).unwrap();
+
+        let loaded = Snapshot::open(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), snapshot);
+    }
+
+    #[test]
+    fn diffs_loaded_snapshots() {
+        let before = sample();
+        let mut after = Snapshot::parse("range\t180001001\t\tadbe0000\n").unwrap();
+        after.add_module(before.modules()[0].clone());
+
+        let changes = before.diff(&after);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].addr, 0x1_8000_1003);
+        assert_eq!((changes[0].old.as_slice(), changes[0].new.as_slice()), ([0xEF].as_slice(), [0x00].as_slice()));
+        assert_eq!(changes[0].symbol.as_deref(), Some("C:\\apps\\tab\there.dll+0x1003"));
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for text in [
+            "range\t1000\t\n",
+            "range\tzz\t\t00\n",
+            "range\t1000\t\t0\n",
+            "module\t1000\tzz\tapp\n",
+            "page\t1000\t\t00\n",
+        ] {
+            assert!(matches!(Snapshot::parse(text), Err(DbgError::InvalidSnapshot(_))), "{text:?}");
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn symbolizes_changes() {
+        use crate::mock::MockTarget;
+
+        let target = MockTarget::new().with_symbol("app!Counter", 0x1_8000_1000);
+        let mut changes = sample().diff(&Snapshot::parse("range\t180001002\t\t0000\nrange\t1000\t\t010203\n").unwrap());
+        for change in &mut changes {
+            change.symbolize(&target);
+        }
+
+        assert_eq!(changes[0].symbol.as_deref(), Some("app!Counter+0x2"));
+
+        let mut change = MemoryChange {
+            addr: 0x1_8000_1000,
+            old: vec![0],
+            new: vec![1],
+            symbol: None,
+        };
+        change.symbolize(&target);
+        assert_eq!(change.symbol.as_deref(), Some("app!Counter"));
+    }
+
+    /// Builds the headers of a 64-bit PE image with a `.text` section at 0x1000 and a `.data`
+    /// section at 0x2000.
+    #[cfg(feature = "mock")]
+    fn image_headers(data_address: u32) -> Vec<u8> {
+        let mut headers = vec![0u8; PAGE_SIZE as usize];
+        headers[..2].copy_from_slice(b"MZ");
+        headers[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        headers[0x40..0x44].copy_from_slice(b"PE\0\0");
+
+        // NumberOfSections, SizeOfOptionalHeader and SizeOfImage
+        headers[0x46..0x48].copy_from_slice(&2u16.to_le_bytes());
+        headers[0x54..0x56].copy_from_slice(&0xF0u16.to_le_bytes());
+        headers[0x90..0x94].copy_from_slice(&0x3000u32.to_le_bytes());
+
+        for (index, (name, address, size)) in [(b".text", 0x1000u32, 0x10u32), (b".data", data_address, 0x08)]
+            .iter()
+            .enumerate()
+        {
+            let entry = 0x148 + index * 40;
+            headers[entry..entry + 5].copy_from_slice(*name);
+            headers[entry + 8..entry + 12].copy_from_slice(&size.to_le_bytes());
+            headers[entry + 12..entry + 16].copy_from_slice(&address.to_le_bytes());
+        }
+
+        headers
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn captures_module_sections() {
+        use crate::mock::MockTarget;
+
+        let base = 0x1_4000_0000;
+        let target = MockTarget::new()
+            .with_memory(base, image_headers(0x2000))
+            .with_memory(base + 0x1000, vec![0x90; 0x10])
+            .with_memory(base + 0x2000, vec![1, 2, 3, 4]);
+
+        let mut snapshot = Snapshot::new();
+        snapshot.capture_module(&target, "app", base).unwrap();
+
+        assert_eq!(
+            snapshot.modules(),
+            [SnapshotModule {
+                name: "app".to_string(),
+                base,
+                size: 0x3000,
+            }]
+        );
+
+        // The unmapped end of `.data` is left out
+        let ranges = snapshot.ranges();
+        assert_eq!(ranges.len(), 2);
+        assert_eq!((ranges[0].base, ranges[0].label.as_deref()), (base + 0x1000, Some("app!.text")));
+        assert_eq!(ranges[0].bytes, vec![0x90; 0x10]);
+        assert_eq!((ranges[1].base, ranges[1].label.as_deref()), (base + 0x2000, Some("app!.data")));
+        assert_eq!(ranges[1].bytes, vec![1, 2, 3, 4]);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn rejects_malformed_images() {
+        use crate::mock::MockTarget;
+
+        let mut headers = image_headers(0x2000);
+        headers[0x40] = b'N';
+        let target = MockTarget::new().with_memory(0x1000, headers);
+        assert!(matches!(
+            Snapshot::new().capture_module(&target, "app", 0x1000),
+            Err(DbgError::InvalidImage(0x1000))
+        ));
+
+        // A section past the end of the address space
+        let base = u64::MAX - (PAGE_SIZE - 1);
+        let target = MockTarget::new().with_memory(base, image_headers(0x1000));
+        assert!(matches!(Snapshot::new().capture_module(&target, "app", base), Err(DbgError::InvalidImage(b)) if b == base));
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn capture_splits_around_unreadable_pages() {
+        use crate::mock::MockTarget;
+
+        let target = MockTarget::new()
+            .with_memory(0x1000, vec![1; 0x1000])
+            .with_memory(0x2000, vec![2; 0x800])
+            .with_memory(0x4000, vec![4; 0x1000]);
+
+        let mut snapshot = Snapshot::new();
+        snapshot.capture_range(&target, 0x1800, 0x3000, Some("heap")).unwrap();
+
+        // Adjacent regions merge, the unreadable end of a page and the page after it are skipped
+        let ranges = snapshot
+            .ranges()
+            .iter()
+            .map(|range| (range.base, range.bytes.len(), range.label.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, [(0x1800, 0x1000, Some("heap")), (0x4000, 0x800, Some("heap"))]);
+        assert_eq!(snapshot.ranges()[0].bytes[0x7FF..0x801], [1, 2]);
+    }
+}
@@ -0,0 +1,72 @@
+//! Addressing the different memory spaces exposed by the debugging engine.
+
+/// The caching policy used when accessing physical memory, matching the `DEBUG_PHYSICAL_*` constants.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PhysicalCaching {
+    /// Let the engine pick the caching attributes.
+    #[default]
+    Default = 0,
+
+    /// Access the memory as cached.
+    Cached = 1,
+
+    /// Access the memory as uncached.
+    Uncached = 2,
+
+    /// Access the memory as write-combined.
+    WriteCombined = 3,
+}
+
+/// A memory space of the target, used with `Dbg::read` and `Dbg::write`.
+///
+/// Most spaces other than [`MemorySpace::Virtual`] are only available in kernel-mode sessions.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut buffer = [0u8; 8];
+/// dbg.read(MemorySpace::Physical(PhysicalCaching::Uncached), 0x1000, &mut buffer)?;
+/// dbg.read(MemorySpace::Msr, 0xC000_0082, &mut buffer)?; // IA32_LSTAR
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemorySpace {
+    /// The virtual address space of the current process.
+    Virtual,
+
+    /// Physical memory, accessed with the given caching policy.
+    Physical(PhysicalCaching),
+
+    /// The control space of a processor, e.g. the `KPCR`/`KPRCB` on x86 and x64.
+    Control {
+        /// The processor whose control space is accessed.
+        processor: u32,
+    },
+
+    /// System I/O ports.
+    Io {
+        /// The `INTERFACE_TYPE` of the bus, e.g. 1 for ISA.
+        interface_type: u32,
+
+        /// The number of the bus.
+        bus_number: u32,
+
+        /// The address space, 1 for I/O ports.
+        address_space: u32,
+    },
+
+    /// Bus-specific data, e.g. PCI configuration space.
+    BusData {
+        /// The `BUS_DATA_TYPE` of the bus, e.g. 4 for PCI configuration space.
+        bus_data_type: u32,
+
+        /// The number of the bus.
+        bus_number: u32,
+
+        /// The bus-specific slot number.
+        slot_number: u32,
+    },
+
+    /// Model-specific registers. The address is the MSR number and the buffer must be 8 bytes long.
+    Msr,
+}
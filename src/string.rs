@@ -0,0 +1,219 @@
+//! Reading null-terminated and counted strings from target memory.
+
+use crate::{error::DbgError, ptr::PointerWidth, target::Memory};
+
+/// A string read from the target, flagged when it did not fit the requested bounds.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TargetString {
+    /// The decoded text, invalid sequences replaced with `U+FFFD`.
+    pub text: String,
+
+    /// Whether the string was cut short, either because no terminator was found within
+    /// the maximum length or because part of its buffer could not be read.
+    pub truncated: bool,
+}
+
+impl TargetString {
+    /// Builds a string from narrow characters.
+    pub(crate) fn from_narrow(chars: &[u8], truncated: bool) -> Self {
+        Self {
+            text: String::from_utf8_lossy(chars).to_string(),
+            truncated,
+        }
+    }
+
+    /// Builds a string from UTF-16 code units.
+    pub(crate) fn from_wide(chars: &[u16], truncated: bool) -> Self {
+        Self {
+            text: String::from_utf16_lossy(chars),
+            truncated,
+        }
+    }
+}
+
+/// Reads the characters of a null-terminated string of `char_size`-byte characters,
+/// keeping at most `max_len` of them.
+pub(crate) fn read_terminated<M>(memory: &M, addr: u64, max_len: usize, char_size: usize) -> Result<(Vec<u8>, bool), DbgError>
+where
+    M: Memory + ?Sized,
+{
+    // One extra character tells a string of exactly `max_len` characters from a longer one
+    let size = max_len
+        .checked_add(1)
+        .and_then(|len| len.checked_mul(char_size))
+        .ok_or(DbgError::InvalidSize(max_len))?;
+
+    let mut buffer = vec![0u8; size];
+    let read = memory.read_vaddr(addr, &mut buffer)? / char_size;
+    if read == 0 {
+        return Err(DbgError::InvalidSize(read));
+    }
+
+    let terminator = buffer[..read * char_size]
+        .chunks_exact(char_size)
+        .position(|c| c.iter().all(|&b| b == 0));
+
+    // Without a terminator the string either goes on past `max_len` or runs into unreadable memory
+    let (len, truncated) = match terminator {
+        Some(len) => (len, false),
+        None => (read.min(max_len), true),
+    };
+
+    buffer.truncate(len * char_size);
+    Ok((buffer, truncated))
+}
+
+/// Reads the buffer of a `UNICODE_STRING` or `ANSI_STRING` laid out for pointers of `width`.
+pub(crate) fn read_counted<M>(memory: &M, addr: u64, width: PointerWidth) -> Result<(Vec<u8>, bool), DbgError>
+where
+    M: Memory + ?Sized,
+{
+    // `Length` and `MaximumLength` are followed by the buffer pointer, aligned to its size
+    let mut header = [0u8; 16];
+    let header = &mut header[..width.size() * 2];
+    let read = memory.read_vaddr(addr, header)?;
+    if read < header.len() {
+        return Err(DbgError::PartialRead {
+            requested: header.len(),
+            read,
+        });
+    }
+
+    let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+    let mut ptr = [0u8; 8];
+    ptr[..width.size()].copy_from_slice(&header[width.size()..]);
+
+    let mut buffer = vec![0u8; length];
+    if length == 0 {
+        return Ok((buffer, false));
+    }
+
+    let read = memory.read_vaddr(u64::from_le_bytes(ptr), &mut buffer)?;
+    buffer.truncate(read);
+
+    Ok((buffer, read < length))
+}
+
+/// Converts little-endian bytes into UTF-16 code units, dropping a trailing odd byte.
+pub(crate) fn to_wide(bytes: &[u8]) -> Vec<u16> {
+    bytes.as_chunks::<2>().0.iter().map(|&c| u16::from_le_bytes(c)).collect()
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockTarget;
+
+    fn string(text: &str, truncated: bool) -> TargetString {
+        TargetString {
+            text: text.to_string(),
+            truncated,
+        }
+    }
+
+    fn wide(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    /// Builds a 64-bit `UNICODE_STRING` header, with the padding that aligns its buffer pointer.
+    fn header64(length: u16, buffer: u64) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&length.to_le_bytes());
+        header.extend_from_slice(&length.to_le_bytes());
+        header.extend_from_slice(&[0xCC; 4]);
+        header.extend_from_slice(&buffer.to_le_bytes());
+        header
+    }
+
+    /// Builds a 32-bit `UNICODE_STRING32` header.
+    fn header32(length: u16, buffer: u32) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&length.to_le_bytes());
+        header.extend_from_slice(&length.to_le_bytes());
+        header.extend_from_slice(&buffer.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn truncates_past_max_len() {
+        let target = MockTarget::new()
+            .with_memory(0x1000, b"abcd\0".to_vec())
+            .with_memory(0x2000, b"abcde\0".to_vec());
+
+        assert_eq!(read_terminated(&target, 0x1000, 4, 1).unwrap(), (b"abcd".to_vec(), false));
+        assert_eq!(read_terminated(&target, 0x2000, 4, 1).unwrap(), (b"abcd".to_vec(), true));
+        assert_eq!(target.read_cstr_max(0x1000, 4).unwrap(), string("abcd", false));
+        assert_eq!(target.read_cstr_max(0x2000, 4).unwrap(), string("abcd", true));
+    }
+
+    #[test]
+    fn truncates_wide_strings_past_max_len() {
+        let mut bytes = wide("abcd");
+        bytes.extend_from_slice(&[0, 0]);
+        let target = MockTarget::new().with_memory(0x1000, bytes);
+
+        assert_eq!(target.read_wstr(0x1000, 4).unwrap(), string("abcd", false));
+        assert_eq!(target.read_wstr(0x1000, 3).unwrap(), string("abc", true));
+    }
+
+    #[test]
+    fn unterminated_strings_stop_at_unmapped_memory() {
+        let target = MockTarget::new()
+            .with_memory(0x1000, b"abc".to_vec())
+            .with_memory(0x2000, wide("xyz"));
+
+        assert_eq!(target.read_cstr_max(0x1000, 16).unwrap(), string("abc", true));
+        assert_eq!(target.read_wstr(0x2000, 16).unwrap(), string("xyz", true));
+        assert!(matches!(target.read_cstr_max(0x3000, 16), Err(DbgError::InvalidAddress(0x3000))));
+    }
+
+    #[test]
+    fn rejects_overflowing_max_len() {
+        let target = MockTarget::new().with_memory(0x1000, b"abc\0".to_vec());
+
+        assert!(matches!(target.read_cstr_max(0x1000, usize::MAX), Err(DbgError::InvalidSize(usize::MAX))));
+        assert!(matches!(target.read_wstr(0x1000, usize::MAX / 2), Err(DbgError::InvalidSize(_))));
+    }
+
+    #[test]
+    fn reads_unicode_strings_for_both_widths() {
+        let target = MockTarget::new()
+            .with_memory(0x1000, header64(10, 0x2000))
+            .with_memory(0x1100, header32(10, 0x2000))
+            .with_memory(0x2000, wide("hello"));
+
+        assert_eq!(target.read_unicode_string(0x1000).unwrap(), string("hello", false));
+        assert_eq!(target.read_unicode_string_as(0x1100, PointerWidth::Bits32).unwrap(), string("hello", false));
+
+        let target = target.with_pointer_width(PointerWidth::Bits32);
+        assert_eq!(target.read_unicode_string(0x1100).unwrap(), string("hello", false));
+    }
+
+    #[test]
+    fn reads_ansi_strings_for_both_widths() {
+        let target = MockTarget::new()
+            .with_memory(0x1000, header64(5, 0x2000))
+            .with_memory(0x1100, header32(5, 0x2000))
+            .with_memory(0x2000, b"hello world".to_vec());
+
+        assert_eq!(target.read_ansi_string(0x1000).unwrap(), string("hello", false));
+        assert_eq!(target.read_ansi_string_as(0x1100, PointerWidth::Bits32).unwrap(), string("hello", false));
+    }
+
+    #[test]
+    fn counted_strings_stop_at_unmapped_memory() {
+        let target = MockTarget::new()
+            .with_memory(0x1000, header64(10, 0x2000))
+            .with_memory(0x2000, wide("he"));
+
+        assert_eq!(read_counted(&target, 0x1000, PointerWidth::Bits64).unwrap(), (wide("he"), true));
+        assert_eq!(target.read_unicode_string(0x1000).unwrap(), string("he", true));
+    }
+
+    #[test]
+    fn counted_strings_need_a_whole_header() {
+        let target = MockTarget::new().with_memory(0x1000, header64(10, 0x2000)[..12].to_vec());
+
+        assert!(matches!(target.read_unicode_string(0x1000), Err(DbgError::PartialRead { requested: 16, read: 12 })));
+    }
+}
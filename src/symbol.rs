@@ -0,0 +1,127 @@
+//! Types describing symbols resolved by the target.
+
+use std::fmt;
+#[cfg(windows)]
+use crate::Dbg;
+
+/// Size of the name buffer used for each symbol match.
+#[cfg(windows)]
+const MATCH_NAME_SIZE: usize = 1024;
+
+/// A symbol found for an address, split into its module and name.
+///
+/// Returned by `Dbg::symbol_at`, `Dbg::previous_symbol` and `Dbg::next_symbol`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SymbolAt {
+    /// The module the symbol belongs to, e.g. `ntdll`.
+    pub module: String,
+
+    /// The symbol name without the module, empty if only the module is known.
+    pub name: String,
+
+    /// The address of the symbol.
+    pub address: u64,
+
+    /// The distance from the symbol to the queried address, negative when the symbol comes after it.
+    pub displacement: i64,
+}
+
+impl SymbolAt {
+    /// Splits a `module!name` string as returned by the engine.
+    ///
+    /// # Arguments
+    ///
+    /// * `full` - The qualified name.
+    /// * `addr` - The queried address.
+    /// * `displacement` - The distance from the symbol to `addr`.
+    #[cfg(windows)]
+    pub(crate) fn new(full: &str, addr: u64, displacement: u64) -> Self {
+        let (module, name) = full.split_once('!').unwrap_or((full, ""));
+        Self {
+            module: module.to_string(),
+            name: name.to_string(),
+            address: addr.wrapping_sub(displacement),
+            displacement: displacement as i64,
+        }
+    }
+}
+
+impl SymbolAt {
+    /// Returns the symbol qualified with its module, without the displacement, e.g. `ntdll!LdrpHeap`.
+    pub fn qualified_name(&self) -> String {
+        match self.name.as_str() {
+            "" => self.module.clone(),
+            name => format!("{}!{name}", self.module),
+        }
+    }
+}
+
+impl fmt::Display for SymbolAt {
+    /// Formats the symbol like the debugger does, e.g. `ntdll!LdrpHeap+0x10`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.qualified_name())?;
+        match self.displacement {
+            0 => Ok(()),
+            d if d < 0 => write!(f, "-{:#x}", d.unsigned_abs()),
+            d => write!(f, "+{d:#x}"),
+        }
+    }
+}
+
+/// An iterator over the symbols matching a wildcard pattern, created by `Dbg::symbols_matching`.
+///
+/// Yields each symbol's qualified name and address. The engine's match handle is released when
+/// the iterator is dropped.
+#[cfg(windows)]
+#[derive(Debug)]
+pub struct SymbolMatches<'a> {
+    /// The session being queried.
+    dbg: &'a Dbg,
+
+    /// The handle returned by `StartSymbolMatch`.
+    handle: u64,
+}
+
+#[cfg(windows)]
+impl<'a> SymbolMatches<'a> {
+    /// Wraps a handle returned by `StartSymbolMatch`.
+    pub(crate) fn new(dbg: &'a Dbg, handle: u64) -> Self {
+        Self { dbg, handle }
+    }
+}
+
+#[cfg(windows)]
+impl Iterator for SymbolMatches<'_> {
+    type Item = (String, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buffer = vec![0u8; MATCH_NAME_SIZE];
+        let (mut size, mut offset) = (0u32, 0u64);
+        unsafe {
+            self.dbg
+                .symbols
+                .GetNextSymbolMatch(self.handle, Some(&mut buffer), Some(&mut size), Some(&mut offset))
+                .ok()?
+        };
+
+        // The match has moved on even if the name was cut, so ask for it by address instead
+        if size as usize > buffer.len()
+            && let Ok(Some(symbol)) = self.dbg.symbol_at(offset)
+            && symbol.displacement == 0
+        {
+            return Some((symbol.to_string(), offset));
+        }
+
+        buffer.truncate(buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len()));
+        Some((String::from_utf8_lossy(&buffer).into_owned(), offset))
+    }
+}
+
+#[cfg(windows)]
+impl Drop for SymbolMatches<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self.dbg.symbols.EndSymbolMatch(self.handle);
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Backend-agnostic traits describing what a debugging target can do.
+//!
+//! [`Dbg`](crate::Dbg) implements every trait in this module, but code written against
+//! them can run on any backend, such as the in-memory `MockTarget` available behind
+//! the `mock` feature.
+
+use crate::{
+    error::DbgError,
+    pod::{Pod, as_bytes_mut},
+    ptr::{PointerWidth, TargetPtr},
+    string::{TargetString, read_counted, read_terminated, to_wide},
+    types::TypeInfo,
+    value::{DebugValue, Value, ValueType},
+};
+
+/// Maximum number of bytes read by the default [`Memory::read_cstr`] implementation.
+const MAX_CSTR_LEN: usize = 256;
+
+/// Access to the virtual memory of a target.
+pub trait Memory {
+    /// Reads a range of virtual memory into a buffer, returning the number of bytes read.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual address to read from.
+    /// * `buffer` - A mutable slice where the read bytes will be stored.
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError>;
+
+    /// Reads a value of a plain-old-data type from a given virtual memory address.
+    ///
+    /// Fails with [`DbgError::PartialRead`] instead of returning a zero-padded value if only
+    /// part of the value could be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The starting virtual memory address to read from.
+    fn read_type_vaddr<T: Pod>(&self, vaddr: u64) -> Result<T, DbgError>
+    where
+        Self: Sized,
+    {
+        let [value] = self.read_array::<T, 1>(vaddr)?;
+        Ok(value)
+    }
+
+    /// Reads `count` consecutive values of a plain-old-data type.
+    ///
+    /// Fails with [`DbgError::PartialRead`] instead of returning zeroed values if only part of
+    /// the range could be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The virtual address of the first value.
+    /// * `count` - The number of values to read.
+    fn read_slice<T: Pod>(&self, vaddr: u64, count: usize) -> Result<Vec<T>, DbgError>
+    where
+        Self: Sized,
+    {
+        if size_of::<T>() == 0 {
+            return Err(DbgError::InvalidSize(0));
+        }
+
+        // SAFETY: every bit pattern, including all zeroes, is a valid `T`
+        let mut values = vec![unsafe { std::mem::zeroed::<T>() }; count];
+        read_exact(self, vaddr, as_bytes_mut(&mut values))?;
+
+        Ok(values)
+    }
+
+    /// Reads a fixed-size array of a plain-old-data type.
+    ///
+    /// Fails with [`DbgError::PartialRead`] instead of returning zeroed values if only part of
+    /// the array could be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The virtual address of the first element.
+    fn read_array<T: Pod, const N: usize>(&self, vaddr: u64) -> Result<[T; N], DbgError>
+    where
+        Self: Sized,
+    {
+        if size_of::<[T; N]>() == 0 {
+            return Err(DbgError::InvalidSize(0));
+        }
+
+        // SAFETY: every bit pattern, including all zeroes, is a valid `T`
+        let mut values = [unsafe { std::mem::zeroed::<T>() }; N];
+        read_exact(self, vaddr, as_bytes_mut(&mut values))?;
+
+        Ok(values)
+    }
+
+    /// Returns the width of pointers in the current process of the target.
+    ///
+    /// The default implementation assumes 64-bit pointers.
+    fn pointer_width(&self) -> Result<PointerWidth, DbgError> {
+        Ok(PointerWidth::Bits64)
+    }
+
+    /// Reads a pointer sized according to [`Memory::pointer_width`].
+    ///
+    /// # Arguments
+    ///
+    /// * `vaddr` - The virtual address of the pointer.
+    fn read_ptr(&self, vaddr: u64) -> Result<TargetPtr, DbgError> {
+        let width = self.pointer_width()?;
+        let mut buffer = [0u8; 8];
+        let read = self.read_vaddr(vaddr, &mut buffer[..width.size()])?;
+        if read < width.size() {
+            return Err(DbgError::PartialRead {
+                requested: width.size(),
+                read,
+            });
+        }
+
+        Ok(TargetPtr::new(u64::from_le_bytes(buffer), width))
+    }
+
+    /// Reads a null-terminated C string from a specific virtual memory address.
+    ///
+    /// The default implementation reads at most 256 bytes through [`Memory::read_vaddr`] and
+    /// silently drops the rest, see [`Memory::read_cstr_max`] to detect truncation.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the null-terminated string to read.
+    fn read_cstr(&self, addr: u64) -> Result<String, DbgError> {
+        let mut buffer = vec![0u8; MAX_CSTR_LEN];
+        let read = self.read_vaddr(addr, &mut buffer)?;
+        if read == 0 {
+            return Err(DbgError::InvalidSize(read));
+        }
+
+        // Stop at the null terminator, or keep everything that was read if there is none
+        let len = buffer[..read].iter().position(|&b| b == 0).unwrap_or(read);
+        buffer.truncate(len);
+
+        Ok(String::from_utf8_lossy(&buffer).to_string())
+    }
+
+    /// Reads a null-terminated narrow string of at most `max_len` bytes.
+    ///
+    /// Unlike [`Memory::read_cstr`], truncation is reported through [`TargetString::truncated`].
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the string.
+    /// * `max_len` - The maximum number of characters to keep.
+    fn read_cstr_max(&self, addr: u64, max_len: usize) -> Result<TargetString, DbgError> {
+        let (chars, truncated) = read_terminated(self, addr, max_len, 1)?;
+        Ok(TargetString::from_narrow(&chars, truncated))
+    }
+
+    /// Reads a null-terminated UTF-16 string of at most `max_len` characters.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the string.
+    /// * `max_len` - The maximum number of UTF-16 code units to keep.
+    fn read_wstr(&self, addr: u64, max_len: usize) -> Result<TargetString, DbgError> {
+        let (chars, truncated) = read_terminated(self, addr, max_len, 2)?;
+        Ok(TargetString::from_wide(&to_wide(&chars), truncated))
+    }
+
+    /// Reads a `UNICODE_STRING` laid out for the pointer width of the target.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the `UNICODE_STRING` structure.
+    fn read_unicode_string(&self, addr: u64) -> Result<TargetString, DbgError> {
+        self.read_unicode_string_as(addr, self.pointer_width()?)
+    }
+
+    /// Reads a `UNICODE_STRING` laid out for the given pointer width, e.g. a
+    /// `UNICODE_STRING32` from the 32-bit PEB of a WOW64 process.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the `UNICODE_STRING` structure.
+    /// * `width` - The width of the `Buffer` pointer.
+    fn read_unicode_string_as(&self, addr: u64, width: PointerWidth) -> Result<TargetString, DbgError> {
+        let (bytes, truncated) = read_counted(self, addr, width)?;
+        Ok(TargetString::from_wide(&to_wide(&bytes), truncated))
+    }
+
+    /// Reads an `ANSI_STRING` laid out for the pointer width of the target.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the `ANSI_STRING` structure.
+    fn read_ansi_string(&self, addr: u64) -> Result<TargetString, DbgError> {
+        self.read_ansi_string_as(addr, self.pointer_width()?)
+    }
+
+    /// Reads an `ANSI_STRING` laid out for the given pointer width.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The virtual memory address of the `ANSI_STRING` structure.
+    /// * `width` - The width of the `Buffer` pointer.
+    fn read_ansi_string_as(&self, addr: u64, width: PointerWidth) -> Result<TargetString, DbgError> {
+        let (bytes, truncated) = read_counted(self, addr, width)?;
+        Ok(TargetString::from_narrow(&bytes, truncated))
+    }
+
+    /// Returns a counter that changes whenever the contents of memory may have changed,
+    /// e.g. because the target resumed or was written to.
+    ///
+    /// Caches such as [`CachedMemory`](crate::CachedMemory) compare it between reads.
+    /// The default implementation always returns 0, for memory that never changes.
+    fn generation(&self) -> u64 {
+        0
+    }
+}
+
+/// Fills `buffer` completely from virtual memory, reporting short reads.
+fn read_exact<M: Memory>(memory: &M, vaddr: u64, buffer: &mut [u8]) -> Result<(), DbgError> {
+    let read = memory.read_vaddr(vaddr, buffer)?;
+    if read < buffer.len() {
+        return Err(DbgError::PartialRead {
+            requested: buffer.len(),
+            read,
+        });
+    }
+
+    Ok(())
+}
+
+/// Access to the physical memory of a target.
+///
+/// Implemented for byte slices, where byte `N` of the slice is physical address `N`,
+/// so raw physical images can be used wherever a live target is expected.
+pub trait PhysicalMemory {
+    /// Reads a range of physical memory into a buffer, returning the number of bytes read.
+    ///
+    /// # Arguments
+    ///
+    /// * `paddr` - The starting physical address to read from.
+    /// * `buffer` - A mutable slice where the read bytes will be stored.
+    fn read_paddr(&self, paddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError>;
+}
+
+impl PhysicalMemory for [u8] {
+    fn read_paddr(&self, paddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        let start = usize::try_from(paddr)
+            .ok()
+            .filter(|&start| start < self.len())
+            .ok_or(DbgError::InvalidAddress(paddr))?;
+
+        let len = buffer.len().min(self.len() - start);
+        buffer[..len].copy_from_slice(&self[start..start + len]);
+        Ok(len)
+    }
+}
+
+/// Resolution between symbol names and addresses.
+pub trait Symbols {
+    /// Retrieves the address of a symbol by its name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the symbol, e.g. `ntdll!NtAllocateVirtualMemory`.
+    fn get_symbol_address(&self, name: &str) -> Result<u64, DbgError>;
+
+    /// Resolves a symbol name from a given address, e.g. `ntdll!LdrpHeap`.
+    ///
+    /// The name is that of the closest symbol at or below the address, without the displacement.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to resolve to a symbol name.
+    fn get_symbol_name(&self, addr: u64) -> Result<String, DbgError>;
+}
+
+/// Access to the layout of types described by the target's symbols.
+pub trait Types {
+    /// Resolves the layout of a type and its direct fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The type name, qualified with its module, e.g. `nt!_EPROCESS`.
+    fn type_info(&self, name: &str) -> Result<TypeInfo, DbgError>;
+
+    /// Returns the offset of a field inside a type.
+    ///
+    /// The default implementation looks the field up in [`Types::type_info`].
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - The type name, e.g. `nt!_EPROCESS`.
+    /// * `field` - The field name, e.g. `UniqueProcessId`.
+    fn field_offset(&self, type_name: &str, field: &str) -> Result<u32, DbgError> {
+        Ok(self.type_info(type_name)?.require_field(field)?.offset)
+    }
+}
+
+/// Access to the CPU registers of a target.
+pub trait Registers {
+    /// Retrieves the register indices corresponding to a provided list of names.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The names of the registers to look up.
+    fn reg_indices(&self, names: &[&str]) -> Result<Vec<u32>, DbgError>;
+
+    /// Retrieves the values of the registers corresponding to a provided list of indices.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - The register indices for which the values need to be fetched.
+    fn reg_values(&self, indices: &[u32]) -> Result<Vec<Value>, DbgError>;
+}
+
+/// Execution of debugger commands and expressions.
+pub trait Commands {
+    /// Executes a command in the debugger.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to execute.
+    fn exec(&self, command: &str) -> Result<(), DbgError>;
+
+    /// Evaluates an expression into a [`Value`] of the requested type.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to evaluate.
+    /// * `value_type` - The desired type of the result.
+    fn evaluate(&self, expr: &str, value_type: ValueType) -> Result<Value, DbgError>;
+
+    /// Evaluates an expression and returns the result as the specified type.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to evaluate.
+    fn eval<T>(&self, expr: &str) -> Result<T, DbgError>
+    where
+        T: DebugValue,
+        Self: Sized,
+    {
+        Ok(T::from_value(&self.evaluate(expr, T::value_type())?))
+    }
+}
+
+/// A target providing memory, symbol, register and command access.
+///
+/// This trait is implemented automatically for every type implementing
+/// [`Memory`], [`Symbols`], [`Registers`] and [`Commands`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// fn image_base<T: Target>(target: &T) -> Result<u64, DbgError> {
+///     let peb = target.eval::<u64>("@$peb")?;
+///     target.read_type_vaddr::<u64>(peb + 0x10)
+/// }
+/// ```
+pub trait Target: Memory + Symbols + Registers + Commands {}
+
+impl<T> Target for T where T: Memory + Symbols + Registers + Commands {}
@@ -0,0 +1,133 @@
+//! Binding Rust structures to types of the target, usually through `#[derive(TargetStruct)]`.
+
+use std::marker::PhantomData;
+use crate::{
+    error::DbgError,
+    target::{Memory, Types},
+    types::FieldInfo,
+};
+
+/// A Rust structure whose fields are read from a type of the target.
+///
+/// [`TargetStruct::read`] resolves the fields through [`Types::type_info`] on every call. To
+/// read many instances, resolve them once with [`TargetStruct::bind`] and read through the
+/// returned [`Bound`], binding again after the target's symbols are reloaded.
+///
+/// Implement it with the derive macro, available behind the `derive` feature. Fields marked
+/// with `#[field("Name")]` are read at the offset of `Name`, `#[field]` alone uses the Rust
+/// field name, and other fields are set to their default value. Each read field must have
+/// the size of the target field, or reads fail with [`DbgError::FieldSizeMismatch`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(TargetStruct)]
+/// #[target(type = "nt!_EPROCESS")]
+/// struct Process {
+///     #[field("UniqueProcessId")]
+///     pid: u64,
+///
+///     #[field("ImageFileName")]
+///     image: [u8; 15],
+/// }
+///
+/// let process = Process::read(&dbg, dbg.eval::<u64>("poi(nt!PsInitialSystemProcess)")?)?;
+///
+/// // Resolve the fields once when reading many instances
+/// let processes = Process::bind(&dbg)?;
+/// for base in bases {
+///     let process = processes.read(&dbg, base)?;
+/// }
+/// ```
+pub trait TargetStruct: Sized {
+    /// The type on the target, qualified with its module, e.g. `nt!_EPROCESS`.
+    const TYPE_NAME: &'static str;
+
+    /// The target fields read by [`TargetStruct::from_fields`], in order.
+    const FIELDS: &'static [&'static str];
+
+    /// Builds the structure from the instance at `base`, given the layout of each of
+    /// [`TargetStruct::FIELDS`].
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory holding the instance.
+    /// * `base` - The address of the instance.
+    /// * `fields` - The layout of the fields, in the order of [`TargetStruct::FIELDS`].
+    fn from_fields<M: Memory>(memory: &M, base: u64, fields: &[FieldInfo]) -> Result<Self, DbgError>;
+
+    /// Resolves the layout of [`TargetStruct::FIELDS`] on a target.
+    ///
+    /// Fails with [`DbgError::FieldNotFound`] naming the type and the field if the target's
+    /// type has no such field.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target whose symbols describe the type.
+    fn fields<T>(target: &T) -> Result<Vec<FieldInfo>, DbgError>
+    where
+        T: Types + ?Sized,
+    {
+        let info = target.type_info(Self::TYPE_NAME)?;
+        Self::FIELDS.iter().map(|field| info.require_field(field).cloned()).collect()
+    }
+
+    /// Resolves the fields once, returning a [`Bound`] that reads instances without
+    /// resolving them again.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target whose symbols describe the type.
+    fn bind<T>(target: &T) -> Result<Bound<Self>, DbgError>
+    where
+        T: Types + ?Sized,
+    {
+        Ok(Bound {
+            fields: Self::fields(target)?,
+            marker: PhantomData,
+        })
+    }
+
+    /// Reads the structure from the instance at `base`, resolving its fields first.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target holding the instance and describing its type.
+    /// * `base` - The address of the instance.
+    fn read<T>(target: &T, base: u64) -> Result<Self, DbgError>
+    where
+        T: Memory + Types,
+    {
+        Self::bind(target)?.read(target, base)
+    }
+}
+
+/// The fields of a [`TargetStruct`] resolved on a target, see [`TargetStruct::bind`].
+///
+/// The layout is not tied to the target it was resolved from, so the caller decides how long
+/// to keep it, e.g. until the symbols are reloaded or another target is debugged.
+#[derive(Debug, Clone)]
+pub struct Bound<S> {
+    /// The layout of [`TargetStruct::FIELDS`], in order.
+    fields: Vec<FieldInfo>,
+
+    /// The structure read through the layout.
+    marker: PhantomData<fn() -> S>,
+}
+
+impl<S: TargetStruct> Bound<S> {
+    /// Returns the layout of [`TargetStruct::FIELDS`], in order.
+    pub fn fields(&self) -> &[FieldInfo] {
+        &self.fields
+    }
+
+    /// Reads the structure from the instance at `base`.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory holding the instance.
+    /// * `base` - The address of the instance.
+    pub fn read<M: Memory>(&self, memory: &M, base: u64) -> Result<S, DbgError> {
+        S::from_fields(memory, base, &self.fields)
+    }
+}
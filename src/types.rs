@@ -0,0 +1,104 @@
+//! Describing the layout of types known to the target's symbols.
+
+use crate::{error::DbgError, pod::Pod, target::Memory};
+
+/// A field of a structure or union.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldInfo {
+    /// The field name, e.g. `UniqueProcessId`.
+    pub name: String,
+
+    /// The offset of the field from the start of the containing type.
+    pub offset: u32,
+
+    /// The engine's identifier for the type of the field.
+    pub type_id: u32,
+
+    /// The size of the field in bytes, which is the size of the underlying type for bit fields.
+    pub size: u32,
+}
+
+/// The layout of a type, resolved once from the target's symbols.
+///
+/// Returned by [`Types::type_info`](crate::Types::type_info).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let eprocess = dbg.type_info("nt!_EPROCESS")?;
+/// let pid = eprocess.read_field::<u64, _>(&dbg, process, "UniqueProcessId")?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeInfo {
+    /// The type name as it was looked up, e.g. `nt!_EPROCESS`.
+    pub name: String,
+
+    /// The base address of the module defining the type.
+    pub module: u64,
+
+    /// The engine's identifier for the type.
+    pub type_id: u32,
+
+    /// The size of the type in bytes.
+    pub size: u32,
+
+    /// The direct fields of the type, in declaration order.
+    pub fields: Vec<FieldInfo>,
+}
+
+impl TypeInfo {
+    /// Returns the field with the given name, if the type has one.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The field name.
+    pub fn field(&self, name: &str) -> Option<&FieldInfo> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Returns the field with the given name, failing with [`DbgError::FieldNotFound`] if the
+    /// type has none.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The field name.
+    pub fn require_field(&self, name: &str) -> Result<&FieldInfo, DbgError> {
+        self.field(name).ok_or_else(|| DbgError::FieldNotFound {
+            type_name: self.name.clone(),
+            field: name.to_string(),
+        })
+    }
+
+    /// Returns the address of a field of the instance at `base`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The address of the instance.
+    /// * `name` - The field name.
+    pub fn field_address(&self, base: u64, name: &str) -> Result<u64, DbgError> {
+        Ok(base.wrapping_add(u64::from(self.require_field(name)?.offset)))
+    }
+
+    /// Reads a field of the instance at `base`.
+    ///
+    /// Fails with [`DbgError::InvalidSize`] if `T` is larger than the field.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory holding the instance.
+    /// * `base` - The address of the instance.
+    /// * `name` - The field name.
+    pub fn read_field<T, M>(&self, memory: &M, base: u64, name: &str) -> Result<T, DbgError>
+    where
+        T: Pod,
+        M: Memory,
+    {
+        let field = self.require_field(name)?;
+        if size_of::<T>() > field.size as usize {
+            return Err(DbgError::InvalidSize(size_of::<T>()));
+        }
+
+        let [value] = memory.read_array::<T, 1>(base.wrapping_add(u64::from(field.offset)))?;
+        Ok(value)
+    }
+}
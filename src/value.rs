@@ -0,0 +1,284 @@
+//! Platform-neutral representation of register and expression values.
+
+#[cfg(windows)]
+use windows::Win32::System::Diagnostics::Debug::Extensions::*;
+
+/// The type of a [`Value`], matching the `DEBUG_VALUE_*` constants of the debugging engine.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    /// No particular type, the value is returned as the engine computed it.
+    Invalid = 0,
+
+    /// An 8-bit integer.
+    Int8 = 1,
+
+    /// A 16-bit integer.
+    Int16 = 2,
+
+    /// A 32-bit integer.
+    Int32 = 3,
+
+    /// A 64-bit integer.
+    Int64 = 4,
+
+    /// A 32-bit floating-point number.
+    Float32 = 5,
+
+    /// A 64-bit floating-point number.
+    Float64 = 6,
+
+    /// An 80-bit floating-point number.
+    Float80 = 7,
+
+    /// An 82-bit floating-point number.
+    Float82 = 8,
+
+    /// A 128-bit floating-point number.
+    Float128 = 9,
+
+    /// A 64-bit vector.
+    Vector64 = 10,
+
+    /// A 128-bit vector.
+    Vector128 = 11,
+}
+
+impl TryFrom<u32> for ValueType {
+    type Error = u32;
+
+    /// Converts a raw `DEBUG_VALUE_*` constant into a [`ValueType`].
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => ValueType::Invalid,
+            1 => ValueType::Int8,
+            2 => ValueType::Int16,
+            3 => ValueType::Int32,
+            4 => ValueType::Int64,
+            5 => ValueType::Float32,
+            6 => ValueType::Float64,
+            7 => ValueType::Float80,
+            8 => ValueType::Float82,
+            9 => ValueType::Float128,
+            10 => ValueType::Vector64,
+            11 => ValueType::Vector128,
+            other => return Err(other),
+        })
+    }
+}
+
+/// A register or expression value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    /// An 8-bit integer.
+    I8(u8),
+
+    /// A 16-bit integer.
+    I16(u16),
+
+    /// A 32-bit integer.
+    I32(u32),
+
+    /// A 64-bit integer.
+    I64(u64),
+
+    /// A 32-bit floating-point number.
+    F32(f32),
+
+    /// A 64-bit floating-point number.
+    F64(f64),
+
+    /// Any other type, such as extended precision floats and vectors, kept as raw bytes.
+    Raw(ValueType, [u8; 24]),
+}
+
+impl Value {
+    /// Returns the type of the value.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Value::I8(_) => ValueType::Int8,
+            Value::I16(_) => ValueType::Int16,
+            Value::I32(_) => ValueType::Int32,
+            Value::I64(_) => ValueType::Int64,
+            Value::F32(_) => ValueType::Float32,
+            Value::F64(_) => ValueType::Float64,
+            Value::Raw(value_type, _) => *value_type,
+        }
+    }
+
+    /// Returns the value as a 64-bit integer, truncating floats and reading the
+    /// low 8 bytes of raw values.
+    pub fn as_u64(&self) -> u64 {
+        match *self {
+            Value::I8(v) => v as u64,
+            Value::I16(v) => v as u64,
+            Value::I32(v) => v as u64,
+            Value::I64(v) => v,
+            Value::F32(v) => v as u64,
+            Value::F64(v) => v as u64,
+            Value::Raw(_, raw) => u64::from_le_bytes(raw[..8].try_into().unwrap_or_default()),
+        }
+    }
+
+    /// Returns the value as a 64-bit float, converting integers and reading the
+    /// low 8 bytes of raw values as an IEEE 754 double.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Value::F32(v) => v as f64,
+            Value::F64(v) => v,
+            Value::Raw(_, raw) => f64::from_le_bytes(raw[..8].try_into().unwrap_or_default()),
+            _ => self.as_u64() as f64,
+        }
+    }
+}
+
+#[cfg(windows)]
+impl From<&Value> for DEBUG_VALUE {
+    /// Converts a [`Value`] back into the [`DEBUG_VALUE`] the engine would have returned.
+    fn from(val: &Value) -> Self {
+        let mut raw = [0u8; 24];
+        match *val {
+            Value::I8(v) => raw[..1].copy_from_slice(&v.to_le_bytes()),
+            Value::I16(v) => raw[..2].copy_from_slice(&v.to_le_bytes()),
+            Value::I32(v) => raw[..4].copy_from_slice(&v.to_le_bytes()),
+            Value::I64(v) => raw[..8].copy_from_slice(&v.to_le_bytes()),
+            Value::F32(v) => raw[..4].copy_from_slice(&v.to_le_bytes()),
+            Value::F64(v) => raw[..8].copy_from_slice(&v.to_le_bytes()),
+            Value::Raw(_, bytes) => raw = bytes,
+        }
+
+        let mut debug_value = DEBUG_VALUE {
+            Type: val.value_type() as u32,
+            ..Default::default()
+        };
+        debug_value.Anonymous.RawBytes = raw;
+        debug_value
+    }
+}
+
+#[cfg(windows)]
+impl From<&DEBUG_VALUE> for Value {
+    /// Converts a [`DEBUG_VALUE`] returned by the debugging engine according to its `Type`.
+    fn from(val: &DEBUG_VALUE) -> Self {
+        unsafe {
+            match val.Type {
+                DEBUG_VALUE_INT8 => Value::I8(val.Anonymous.I8),
+                DEBUG_VALUE_INT16 => Value::I16(val.Anonymous.I16),
+                DEBUG_VALUE_INT32 => Value::I32(val.Anonymous.I32),
+                DEBUG_VALUE_INT64 => Value::I64(val.Anonymous.Anonymous.I64),
+                DEBUG_VALUE_FLOAT32 => Value::F32(val.Anonymous.F32),
+                DEBUG_VALUE_FLOAT64 => Value::F64(val.Anonymous.F64),
+                other => Value::Raw(ValueType::try_from(other).unwrap_or(ValueType::Invalid), val.Anonymous.RawBytes),
+            }
+        }
+    }
+}
+
+/// A trait to extract a value from a [`Value`].
+///
+/// On Windows, implementations provide either [`DebugValue::from_value`] or
+/// [`DebugValue::from_debug_value`], each defaulting to the other, so implementations written
+/// against `DEBUG_VALUE` keep working. Elsewhere [`DebugValue::from_value`] is required.
+pub trait DebugValue: Sized {
+    /// The corresponding `DEBUG_VALUE_*` type for this type, e.g. `ValueType::Int64 as u32`.
+    const VALUE_TYPE: u32;
+
+    /// Extracts the value from a [`Value`].
+    ///
+    /// # Arguments
+    ///
+    /// * `val` - A reference to the [`Value`] to be converted.
+    #[cfg(windows)]
+    fn from_value(val: &Value) -> Self {
+        Self::from_debug_value(&DEBUG_VALUE::from(val))
+    }
+
+    /// Extracts the value from a [`Value`].
+    ///
+    /// # Arguments
+    ///
+    /// * `val` - A reference to the [`Value`] to be converted.
+    #[cfg(not(windows))]
+    fn from_value(val: &Value) -> Self;
+
+    /// Extracts the value from a `DEBUG_VALUE`.
+    ///
+    /// # Arguments
+    ///
+    /// * `val` - A reference to the `DEBUG_VALUE` to be converted.
+    #[cfg(windows)]
+    fn from_debug_value(val: &DEBUG_VALUE) -> Self {
+        Self::from_value(&Value::from(val))
+    }
+
+    /// Returns [`DebugValue::VALUE_TYPE`] as a [`ValueType`], falling back to
+    /// [`ValueType::Invalid`] for unknown numbers.
+    fn value_type() -> ValueType {
+        ValueType::try_from(Self::VALUE_TYPE).unwrap_or(ValueType::Invalid)
+    }
+}
+
+impl DebugValue for u64 {
+    /// Specifies that the value should be interpreted as a 64-bit integer.
+    const VALUE_TYPE: u32 = ValueType::Int64 as u32;
+
+    /// Extracts a 64-bit integer [`u64`] from a [`Value`].
+    fn from_value(val: &Value) -> Self {
+        val.as_u64()
+    }
+}
+
+impl DebugValue for u32 {
+    /// Specifies that the value should be interpreted as a 32-bit integer.
+    const VALUE_TYPE: u32 = ValueType::Int32 as u32;
+
+    /// Extracts a 32-bit integer [`u32`] from a [`Value`].
+    fn from_value(val: &Value) -> Self {
+        val.as_u64() as u32
+    }
+}
+
+impl DebugValue for f64 {
+    /// Specifies that the value should be interpreted as a 64-bit floating-point number.
+    const VALUE_TYPE: u32 = ValueType::Float64 as u32;
+
+    /// Extracts a 64-bit floating-point value [`f64`] from a [`Value`].
+    fn from_value(val: &Value) -> Self {
+        val.as_f64()
+    }
+}
+
+impl DebugValue for f32 {
+    /// Specifies that the value should be interpreted as a 32-bit floating-point number.
+    const VALUE_TYPE: u32 = ValueType::Float32 as u32;
+
+    /// Extracts a 32-bit floating-point value [`f32`] from a [`Value`].
+    fn from_value(val: &Value) -> Self {
+        match *val {
+            Value::F32(v) => v,
+            _ => val.as_f64() as f32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_values() {
+        assert_eq!(u64::value_type(), ValueType::Int64);
+        assert_eq!(u64::from_value(&Value::I32(7)), 7);
+        assert_eq!(u32::from_value(&Value::I64(0x1_0000_0002)), 2);
+        assert_eq!(f32::from_value(&Value::F32(1.5)), 1.5);
+        assert_eq!(f64::from_value(&Value::I8(3)), 3.0);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn round_trips_debug_values() {
+        for value in [Value::I16(0xBEEF), Value::I64(u64::MAX), Value::F64(-2.5)] {
+            assert_eq!(Value::from(&DEBUG_VALUE::from(&value)), value);
+        }
+    }
+}
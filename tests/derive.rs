@@ -0,0 +1,165 @@
+#![cfg(all(feature = "derive", feature = "mock"))]
+
+use std::cell::Cell;
+use dbg_rs::{FieldInfo, Memory, MockTarget, TargetStruct, TypeInfo, Types, error::DbgError};
+
+#[derive(Debug, TargetStruct)]
+#[target(type = "app!_PROCESS")]
+struct Process {
+    #[field("UniqueProcessId")]
+    pid: u64,
+
+    #[field]
+    priority: u32,
+
+    image: Vec<u8>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, TargetStruct)]
+#[target(type = "app!_PROCESS")]
+struct NarrowProcess {
+    #[field("UniqueProcessId")]
+    pid: u32,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, TargetStruct)]
+#[target(type = "app!_PROCESS")]
+struct Thread {
+    #[field("ThreadListHead")]
+    head: u64,
+}
+
+fn field(name: &str, offset: u32, size: u32) -> FieldInfo {
+    FieldInfo {
+        name: name.to_string(),
+        offset,
+        type_id: 0,
+        size,
+    }
+}
+
+fn process(fields: Vec<FieldInfo>) -> TypeInfo {
+    TypeInfo {
+        name: "app!_PROCESS".to_string(),
+        module: 0x1000,
+        type_id: 1,
+        size: 0x20,
+        fields,
+    }
+}
+
+fn target() -> MockTarget {
+    let mut bytes = vec![0u8; 0x20];
+    bytes[0x08..0x10].copy_from_slice(&0x1234u64.to_le_bytes());
+    bytes[0x10..0x14].copy_from_slice(&7u32.to_le_bytes());
+
+    MockTarget::new()
+        .with_memory(0x4000, bytes)
+        .with_type(process(vec![field("UniqueProcessId", 0x08, 8), field("priority", 0x10, 4)]))
+}
+
+/// Delegates to a `MockTarget`, counting the types it resolves.
+struct Counting {
+    inner: MockTarget,
+    resolved: Cell<usize>,
+}
+
+impl Memory for Counting {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<usize, DbgError> {
+        self.inner.read_vaddr(vaddr, buffer)
+    }
+}
+
+impl Types for Counting {
+    fn type_info(&self, name: &str) -> Result<TypeInfo, DbgError> {
+        self.resolved.set(self.resolved.get() + 1);
+        self.inner.type_info(name)
+    }
+}
+
+#[test]
+fn read_fields() {
+    let process = Process::read(&target(), 0x4000).unwrap();
+    assert_eq!(process.pid, 0x1234);
+    assert_eq!(process.priority, 7);
+    assert!(process.image.is_empty());
+}
+
+#[test]
+fn missing_field_names_type_and_field() {
+    match Thread::read(&target(), 0x4000) {
+        Err(DbgError::FieldNotFound { type_name, field }) => {
+            assert_eq!(type_name, "app!_PROCESS");
+            assert_eq!(field, "ThreadListHead");
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn size_mismatch() {
+    match NarrowProcess::read(&target(), 0x4000) {
+        Err(DbgError::FieldSizeMismatch {
+            type_name,
+            field,
+            expected,
+            actual,
+        }) => {
+            assert_eq!(type_name, "app!_PROCESS");
+            assert_eq!(field, "UniqueProcessId");
+            assert_eq!((expected, actual), (8, 4));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn fields_are_resolved_per_target() {
+    let first = target();
+    let second = MockTarget::new()
+        .with_memory(0x4000, vec![0u8; 0x20])
+        .with_type(process(vec![field("UniqueProcessId", 0x18, 8), field("priority", 0x00, 4)]));
+
+    assert_eq!(Process::fields(&first).unwrap()[0].offset, 0x08);
+    assert_eq!(Process::fields(&second).unwrap()[0].offset, 0x18);
+    assert_eq!(Process::bind(&first).unwrap().fields()[1].name, "priority");
+}
+
+#[test]
+fn bound_reads_resolve_fields_once() {
+    let target = Counting {
+        inner: target(),
+        resolved: Cell::new(0),
+    };
+
+    let processes = Process::bind(&target).unwrap();
+    assert_eq!(processes.read(&target, 0x4000).unwrap().pid, 0x1234);
+    assert_eq!(processes.read(&target, 0x4000).unwrap().priority, 7);
+    assert_eq!(target.resolved.get(), 1);
+
+    // Unbound reads resolve the fields every time
+    Process::read(&target, 0x4000).unwrap();
+    Process::read(&target, 0x4000).unwrap();
+    assert_eq!(target.resolved.get(), 3);
+}
+
+#[test]
+fn bound_fields_outlive_the_target() {
+    let processes = Process::bind(&target()).unwrap();
+
+    // Another instance laid out the same way, on a target without symbols
+    let mut bytes = vec![0u8; 0x20];
+    bytes[0x08..0x10].copy_from_slice(&0x5678u64.to_le_bytes());
+    let target = MockTarget::new().with_memory(0x8000, bytes);
+
+    assert_eq!(processes.read(&target, 0x8000).unwrap().pid, 0x5678);
+    assert!(Process::read(&target, 0x8000).is_err());
+}
+
+#[test]
+fn ui() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,10 @@
+use dbg_rs::TargetStruct;
+
+#[derive(TargetStruct)]
+#[target(type = "app!_STATE")]
+enum State {
+    Running,
+    Stopped,
+}
+
+fn main() {}
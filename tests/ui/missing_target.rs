@@ -0,0 +1,9 @@
+use dbg_rs::TargetStruct;
+
+#[derive(TargetStruct)]
+struct Process {
+    #[field("UniqueProcessId")]
+    pid: u64,
+}
+
+fn main() {}
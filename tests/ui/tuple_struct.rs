@@ -0,0 +1,7 @@
+use dbg_rs::TargetStruct;
+
+#[derive(TargetStruct)]
+#[target(type = "app!_PAIR")]
+struct Pair(#[field] u32, u32);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use dbg_rs::TargetStruct;
+
+#[derive(TargetStruct)]
+#[target(kind = "app!_PROCESS")]
+struct Process {
+    #[field("UniqueProcessId")]
+    pid: u64,
+}
+
+fn main() {}